@@ -33,10 +33,10 @@ fn _test_tsp()
 
 fn test_tsp_file()
 {
-    let vert_positions = load_vert_positions("data/att48.tsp");
-    let opt_vert_permutation = load_opt_permutation("data/att48.opt.tour");
-    let vert_distances = vert_positions_to_distances(&vert_positions);
-    let vert_count = vert_positions.len();
+    let problem = load_tsplib("data/tsp/att48.tsp");
+    let opt_vert_permutation = load_opt_permutation("data/tsp/att48.opt.tour");
+    let vert_distances = problem.distances();
+    let vert_count = problem.dimension;
     let mut fitness = TspFitness { distances: vert_distances };
     let opt_value = fitness.eval(&opt_vert_permutation);
     let perturbe_mut_op = TspMovePerturbation {};
@@ -58,6 +58,7 @@ fn test_tsp_file()
     println!("Solution:  {:?}", solution.value.vert_perm);
     println!("Fitness:  {:?}", solution.fitness);
     println!("Optimal fitness:  {:?}", opt_value);
+    println!("{}", gap_summary(solution.fitness, tsp_lower_bound(&fitness.distances, LOWER_BOUND_MAX_ITERS)));
     plot(&stats, "out/tsp_file_test.svg", "TSP").unwrap();
 }
 
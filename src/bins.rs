@@ -19,8 +19,12 @@ struct SemCli {
     stats: bool,
     #[arg(short = 'v', long, default_value_t = false)]
     viz: bool,
+    #[arg(short = 'a', long, default_value_t = false)]
+    adaptive: bool,
     #[arg(short = 'r', long, default_value_t = 7)]
     num_rep: usize,
+    #[arg(short = 'i', long, default_value_t = 3000)]
+    num_iters: usize,
     #[arg(short = 'p', long, default_value_t = 64)]
     pop_size: usize
 }
@@ -29,6 +33,7 @@ pub fn hw1()
 {
     let args = Cli::parse();
     create_comparison_graphs(args.num_rep, args.num_iters, args.pop_size);
+    create_crossover_comparison_graphs(args.num_rep, args.num_iters, args.pop_size);
     create_vizualization_graphs(args.num_iters, args.pop_size);
 }
 
@@ -36,6 +41,7 @@ pub fn hw2()
 {
     let args = Cli::parse();
     create_g_funcs_comparison_graphs(args.num_rep, args.num_iters, args.pop_size);
+    create_pso_comparison_graphs(args.num_rep, args.num_iters, args.pop_size);
 }
 
 pub fn sem()
@@ -44,8 +50,9 @@ pub fn sem()
     // TODO: maybe try seeding random generators
     let args = SemCli::parse();
     //gtsp_basic_stats_gen_instance();
-    //gtsp_gen_problem(100, 20, "g2");
-    //gtsp_gen_problem(500, 80, "g3");
+    //gtsp_gen_problem(GtspGenerator::KMeans, 100, 20, "g2");
+    //gtsp_gen_problem(GtspGenerator::KMeans, 500, 80, "g3");
+    //gtsp_gen_problem_from_tsplib("berlin52", 11);
     //gtsp_viz_gen_solution(args.num_iters, args.pop_size);
     //gtsp_find_opt_params_local_search(args.num_rep, args.num_iters, 10);
     //gtsp_find_opt_params_evolutionary_search(args.num_rep, args.num_iters, args.pop_size, 10);
@@ -53,6 +60,7 @@ pub fn sem()
 
     let plot_stats = args.stats;
     let plot_viz = args.viz;
+    let plot_adaptive = args.adaptive;
 
     let num_rep = args.num_rep;
     let pop_size = args.pop_size;
@@ -69,6 +77,11 @@ pub fn sem()
         println!("best algs plots finished");
     }
 
+    if plot_adaptive {
+        gtsp_adaptive_operator_stats(args.num_iters, pop_size);
+        println!("adaptive operators plots finished");
+    }
+
     if plot_viz {
         // viz
         viz_all();
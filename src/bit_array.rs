@@ -1,4 +1,7 @@
-use std::mem;
+use crate::opt_traits::*;
+use crate::opt_data::*;
+
+const BLOCK_BITS: usize = u64::BITS as usize;
 
 #[allow(dead_code)]
 pub struct BitArray<const BLOCK_COUNT: usize>
@@ -7,19 +10,30 @@ pub struct BitArray<const BLOCK_COUNT: usize>
     blocks: [u64; BLOCK_COUNT]
 }
 
-#[allow(dead_code)]
+// Bits packed into 64 bit blocks, bits after bit_count in the last block are always zero
+#[derive(Clone, Debug, PartialEq)]
 pub struct BitVec
 {
     bit_count: usize,
     blocks: Vec<u64>
 }
 
+pub fn blocks_count(bit_count: usize) -> usize {
+    bit_count.div_ceil(BLOCK_BITS)
+}
+
+// mask of the valid bits in the last block
+fn last_block_mask(bit_count: usize) -> u64 {
+    let rem = bit_count % BLOCK_BITS;
+    if rem == 0 { u64::MAX } else { (1u64 << rem) - 1 }
+}
+
 fn get_bit(blocks: &[u64], bit_count: usize, bit_index: usize) -> bool {
     if bit_index >= bit_count {
         panic!("bit index out of range");
     }
-    let in_block_offset = bit_index % mem::size_of::<u64>();
-    let block = blocks[bit_index / mem::size_of::<u64>()];
+    let in_block_offset = bit_index % BLOCK_BITS;
+    let block = blocks[bit_index / BLOCK_BITS];
     (block & (1 << in_block_offset)) > 0
 }
 
@@ -27,12 +41,24 @@ fn set_bit(blocks: &mut [u64], bit_count: usize, bit_index: usize, value: bool)
     if bit_index >= bit_count {
         panic!("bit index out of range");
     }
-    let in_block_offset = bit_index % mem::size_of::<u64>();
-    let block = &mut blocks[bit_index / mem::size_of::<u64>()];
+    let in_block_offset = bit_index % BLOCK_BITS;
+    let block = &mut blocks[bit_index / BLOCK_BITS];
     let shifted_value = (value as u64) << in_block_offset;
     *block = (*block & (!(1 << in_block_offset))) | shifted_value;
 }
 
+// sets bits in range from..to to one
+pub fn set_bit_range(blocks: &mut [u64], from: usize, to: usize) {
+    let mut bit_index = from;
+    while bit_index < to {
+        let offset = bit_index % BLOCK_BITS;
+        let len = (BLOCK_BITS - offset).min(to - bit_index);
+        let mask = if len == BLOCK_BITS { u64::MAX } else { ((1u64 << len) - 1) << offset };
+        blocks[bit_index / BLOCK_BITS] |= mask;
+        bit_index += len;
+    }
+}
+
 pub fn perturbe_bits_naive(blocs: &mut [u64], bit_count: usize, prob: f64) {
     for bit_index in 0..bit_count {
         let old_value = get_bit(blocs, bit_count, bit_index);
@@ -41,37 +67,255 @@ pub fn perturbe_bits_naive(blocs: &mut [u64], bit_count: usize, prob: f64) {
     }
 }
 
-pub fn perturbe_bits(blocs: &mut [u64], one_over_prob_pow_of_2: usize) {
-    for block in blocs {
+// flips every bit with probability 2^-one_over_prob_pow_of_2, bits after bit_count are kept zero
+pub fn perturbe_bits(blocs: &mut [u64], bit_count: usize, one_over_prob_pow_of_2: usize) {
+    let blocks_len = blocs.len();
+    for (b, block) in blocs.iter_mut().enumerate() {
         let mut perturbe_bits = u64::MAX;
         for _ in 0..one_over_prob_pow_of_2 {
             perturbe_bits &= rand::random::<u64>();
         }
-        *block = *block ^ perturbe_bits;
+        if b + 1 == blocks_len {
+            perturbe_bits &= last_block_mask(bit_count);
+        }
+        *block ^= perturbe_bits;
+    }
+}
+
+// flips every bit with probability prob, positions of the flipped bits are sampled
+// by geometric skips so the cost is proportional to the number of flips
+pub fn perturbe_bits_geometric(blocks: &mut [u64], bit_count: usize, prob: f64) {
+    if prob <= 0.0 || bit_count == 0 {
+        return;
+    }
+    if prob >= 1.0 {
+        for block in blocks.iter_mut() {
+            *block = !*block;
+        }
+        let last = blocks.len() - 1;
+        blocks[last] &= last_block_mask(bit_count);
+        return;
+    }
+    let log_not_prob = (1.0 - prob).ln();
+    let mut bit_index = 0usize;
+    loop {
+        let u = 1.0 - rand::random::<f64>();
+        let skip = (u.ln() / log_not_prob).floor();
+        if skip >= (bit_count - bit_index) as f64 {
+            break;
+        }
+        bit_index += skip as usize;
+        blocks[bit_index / BLOCK_BITS] ^= 1 << (bit_index % BLOCK_BITS);
+        bit_index += 1;
     }
 }
 
 impl<const BLOCK_COUNT: usize> BitArray<{BLOCK_COUNT}> {
 
     pub fn zero(bit_count: usize) -> Self {
+        if blocks_count(bit_count) > BLOCK_COUNT {
+            panic!("bit count does not fit into the blocks");
+        }
         let blocks: [u64; BLOCK_COUNT] = [0; BLOCK_COUNT];
         BitArray { bit_count, blocks }
     }
 
+    pub fn len(&self) -> usize {
+        self.bit_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bit_count == 0
+    }
+
+    pub fn get(&self, bit_index: usize) -> bool {
+        get_bit(&self.blocks, self.bit_count, bit_index)
+    }
+
+    pub fn set(&mut self, bit_index: usize, value: bool) {
+        set_bit(&mut self.blocks, self.bit_count, bit_index, value);
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.blocks.iter().map(|block| block.count_ones() as usize).sum()
+    }
 }
 
 impl BitVec {
-    
+    pub fn zero(bit_count: usize) -> Self {
+        BitVec { bit_count, blocks: vec![0; blocks_count(bit_count)] }
+    }
+
+    pub fn random(bit_count: usize) -> Self {
+        let mut blocks: Vec<u64> = (0..blocks_count(bit_count)).map(|_| rand::random::<u64>()).collect();
+        if let Some(last) = blocks.last_mut() {
+            *last &= last_block_mask(bit_count);
+        }
+        BitVec { bit_count, blocks }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bit_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bit_count == 0
+    }
+
+    pub fn blocks(&self) -> &[u64] {
+        &self.blocks
+    }
+
+    pub fn get(&self, bit_index: usize) -> bool {
+        get_bit(&self.blocks, self.bit_count, bit_index)
+    }
+
+    pub fn set(&mut self, bit_index: usize, value: bool) {
+        set_bit(&mut self.blocks, self.bit_count, bit_index, value);
+    }
+
+    pub fn flip(&mut self, bit_index: usize) {
+        let value = self.get(bit_index);
+        self.set(bit_index, !value);
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.blocks.iter().map(|block| block.count_ones() as usize).sum()
+    }
+
+    pub fn perturbe(&mut self, prob: f64) {
+        perturbe_bits_geometric(&mut self.blocks, self.bit_count, prob);
+    }
+
+    // offsprings take the bits of the first parent where the mask is zero and of the second one where it is one
+    pub fn crossover_with_mask(parents: [&BitVec; 2], mask: &[u64]) -> [BitVec; 2] {
+        let mut offspring1 = parents[0].clone();
+        let mut offspring2 = parents[1].clone();
+        for b in 0..mask.len() {
+            let diff = (parents[0].blocks[b] ^ parents[1].blocks[b]) & mask[b];
+            offspring1.blocks[b] ^= diff;
+            offspring2.blocks[b] ^= diff;
+        }
+        [offspring1, offspring2]
+    }
+
+    // 64 bits starting at bit_index, bits after bit_count are zero
+    fn block_at(&self, bit_index: usize) -> u64 {
+        let b = bit_index / BLOCK_BITS;
+        let offset = bit_index % BLOCK_BITS;
+        if b >= self.blocks.len() {
+            return 0;
+        }
+        let mut block = self.blocks[b] >> offset;
+        if offset > 0 && b + 1 < self.blocks.len() {
+            block |= self.blocks[b + 1] << (BLOCK_BITS - offset);
+        }
+        block
+    }
+
+    // number of positions i < len - k where bits i and i + k differ
+    pub fn shifted_diff_count(&self, k: usize) -> usize {
+        let overlap = self.bit_count - k;
+        let mut count = 0;
+        for b in 0..blocks_count(overlap) {
+            let mut diff = self.blocks[b] ^ self.block_at(b * BLOCK_BITS + k);
+            if b + 1 == blocks_count(overlap) {
+                diff &= last_block_mask(overlap);
+            }
+            count += diff.count_ones() as usize;
+        }
+        count
+    }
+}
+
+impl OptData for BitVec {
+    fn dim(&self) -> usize {
+        self.bit_count
+    }
+}
+
+impl From<&NaiveBitVec> for BitVec {
+    fn from(naive: &NaiveBitVec) -> Self {
+        let mut bit_vec = BitVec::zero(naive.bits.len());
+        for i in 0..naive.bits.len() {
+            if naive.bits[i] != 0 {
+                bit_vec.blocks[i / BLOCK_BITS] |= 1 << (i % BLOCK_BITS);
+            }
+        }
+        bit_vec
+    }
+}
+
+impl From<&BitVec> for NaiveBitVec {
+    fn from(bit_vec: &BitVec) -> Self {
+        NaiveBitVec { bits: (0..bit_vec.len()).map(|i| bit_vec.get(i) as u8).collect() }
+    }
+}
+
+pub fn one_max(bits: &BitVec) -> i32 {
+    bits.count_ones() as i32
+}
+
+// correlation C_k of the +-1 sequence is the number of equal pairs minus the number of different pairs
+pub fn labs(bits: &BitVec) -> i32 {
+    let mut es: i32 = 0;
+    for k in 1..bits.len() {
+        let overlap = (bits.len() - k) as i32;
+        let ck_s = overlap - 2 * bits.shifted_diff_count(k) as i32;
+        es += ck_s * ck_s;
+    }
+    es
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::funcs::*;
 
     #[test]
     fn zero() {
         #[allow(unused)]
         let zero = BitArray::<1>::zero(1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_set_bits_in_all_blocks() {
+        let mut bit_vec = BitVec::zero(200);
+        for i in (0..200).step_by(3) {
+            bit_vec.set(i, true);
+        }
+        for i in 0..200 {
+            assert_eq!(bit_vec.get(i), i % 3 == 0);
+        }
+        assert_eq!(bit_vec.count_ones(), 67);
+        let mut bit_array = BitArray::<2>::zero(100);
+        bit_array.set(70, true);
+        assert!(bit_array.get(70) && !bit_array.get(6));
+    }
+
+    #[test]
+    fn test_naive_conversion_and_fitness() {
+        for len in [1, 5, 63, 64, 65, 130] {
+            let bit_vec = BitVec::random(len);
+            let naive = NaiveBitVec::from(&bit_vec);
+            assert_eq!(BitVec::from(&naive), bit_vec);
+            assert_eq!(one_max(&bit_vec), naive_one_max(&naive.bits));
+            assert_eq!(labs(&bit_vec), naive_labs(&naive.bits));
+        }
+    }
+
+    #[test]
+    fn test_perturbe_keeps_unused_bits_zero() {
+        let mut bit_vec = BitVec::zero(70);
+        bit_vec.perturbe(1.0);
+        assert_eq!(bit_vec.count_ones(), 70);
+        for _ in 0..100 {
+            bit_vec.perturbe(0.3);
+            assert_eq!(bit_vec.blocks()[1] & !last_block_mask(70), 0);
+        }
+        let mut empty = BitVec::zero(0);
+        empty.perturbe(1.0);
+        assert_eq!(empty.count_ones(), 0);
+    }
+}
@@ -0,0 +1,134 @@
+use rand::Rng;
+
+use crate::opt_traits::*;
+use crate::opt_data::*;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BoundHandling {
+    // move the value onto the violated bound
+    Clamp,
+    // mirror the value back from the violated bound
+    Reflect,
+    // treat the bounds as toroidal, leaving on one side enters on the other
+    Wrap,
+    // replace the value by a uniformly random one inside the bounds
+    Resample,
+    // place the value halfway between the parent value and the violated bound
+    MidpointToParent,
+    // keep the value, the violation has to be punished by BoundsPenaltyFunc
+    Penalty
+}
+
+pub fn reflect_into_bounds(value: f64, bounds: &Bounds) -> f64 {
+    let size = bounds.upper - bounds.lower;
+    if size <= 0.0 {
+        return bounds.lower;
+    }
+    // fold the value into [0, 2 * size) and mirror the second half
+    let mut offset = (value - bounds.lower).rem_euclid(2.0 * size);
+    if offset > size {
+        offset = 2.0 * size - offset;
+    }
+    bounds.lower + offset
+}
+
+pub fn wrap_into_bounds(value: f64, bounds: &Bounds) -> f64 {
+    let size = bounds.upper - bounds.lower;
+    if size <= 0.0 {
+        return bounds.lower;
+    }
+    bounds.lower + (value - bounds.lower).rem_euclid(size)
+}
+
+pub fn handle_bound(value: f64, parent: f64, bounds: &Bounds, handling: BoundHandling) -> f64 {
+    if value >= bounds.lower && value <= bounds.upper {
+        return value;
+    }
+    match handling {
+        BoundHandling::Clamp => value.clamp(bounds.lower, bounds.upper),
+        BoundHandling::Reflect => reflect_into_bounds(value, bounds),
+        BoundHandling::Wrap => wrap_into_bounds(value, bounds),
+        BoundHandling::Resample => rand::thread_rng().gen_range(bounds.lower..=bounds.upper),
+        BoundHandling::MidpointToParent => {
+            let parent = parent.clamp(bounds.lower, bounds.upper);
+            let bound = if value < bounds.lower { bounds.lower } else { bounds.upper };
+            0.5 * (parent + bound)
+        },
+        BoundHandling::Penalty => value
+    }
+}
+
+// Values outside of bounds are handled, extra values without bounds are left untouched
+pub fn handle_bounds(values: &mut Vec<f64>, parent: &Vec<f64>, bounds: &Vec<Bounds>, handling: BoundHandling) {
+    for i in 0..values.len().min(bounds.len()) {
+        let parent_value = if i < parent.len() { parent[i] } else { values[i] };
+        values[i] = handle_bound(values[i], parent_value, &bounds[i], handling);
+    }
+}
+
+pub fn bounds_violation(values: &Vec<f64>, bounds: &Vec<Bounds>) -> f64 {
+    let mut violation = 0.0;
+    for i in 0..values.len().min(bounds.len()) {
+        violation += (bounds[i].lower - values[i]).max(0.0) + (values[i] - bounds[i].upper).max(0.0);
+    }
+    violation
+}
+
+// Counterpart of BoundHandling::Penalty, adds weighted distance from the bounds to the fitness
+#[derive(Clone)]
+pub struct BoundsPenaltyFunc<RealFunc: FitnessFunc<FloatVec>> {
+    pub real_func: RealFunc,
+    pub bounds: Vec<Bounds>,
+    pub weight: f64
+}
+
+impl<RealFunc: FitnessFunc<FloatVec>> FitnessFunc<FloatVec> for BoundsPenaltyFunc<RealFunc> {
+    fn eval(&self, data: &FloatVec) -> f64 {
+        let violation = bounds_violation(&data.values, &self.bounds);
+        self.real_func.eval(data) + self.weight * violation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const EPSILON: f64 = 1.0e-9;
+
+    fn check_bound(value: f64, parent: f64, handling: BoundHandling, expected: f64) {
+        let bounds = Bounds { lower: -1.0, upper: 3.0 };
+        assert!((handle_bound(value, parent, &bounds, handling) - expected).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_handle_bound() {
+        check_bound(2.0, 0.0, BoundHandling::Clamp, 2.0);
+        check_bound(4.0, 0.0, BoundHandling::Clamp, 3.0);
+        check_bound(-5.0, 0.0, BoundHandling::Clamp, -1.0);
+        check_bound(4.0, 0.0, BoundHandling::Reflect, 2.0);
+        check_bound(-2.0, 0.0, BoundHandling::Reflect, 0.0);
+        check_bound(8.0, 0.0, BoundHandling::Reflect, 0.0);
+        check_bound(4.0, 0.0, BoundHandling::Wrap, 0.0);
+        check_bound(-2.0, 0.0, BoundHandling::Wrap, 2.0);
+        check_bound(4.0, 1.0, BoundHandling::MidpointToParent, 2.0);
+        check_bound(-5.0, 1.0, BoundHandling::MidpointToParent, 0.0);
+        check_bound(4.0, 1.0, BoundHandling::Penalty, 4.0);
+    }
+
+    #[test]
+    fn test_resample_inside() {
+        let bounds = Bounds { lower: -1.0, upper: 3.0 };
+        for _ in 0..100 {
+            let value = handle_bound(10.0, 0.0, &bounds, BoundHandling::Resample);
+            assert!(value >= bounds.lower && value <= bounds.upper);
+        }
+    }
+
+    #[test]
+    fn test_bounds_violation() {
+        let bounds = vec![Bounds { lower: 0.0, upper: 1.0 }; 3];
+        assert_eq!(bounds_violation(&vec![0.5, 0.5, 0.5], &bounds), 0.0);
+        assert!((bounds_violation(&vec![-0.5, 1.5, 0.5], &bounds) - 1.0).abs() < EPSILON);
+    }
+}
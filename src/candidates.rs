@@ -0,0 +1,188 @@
+use std::rc::Rc;
+use rand::Rng;
+
+use crate::*;
+
+// Candidate lists of the k nearest vertices of every vertex, sorted by the distance from the vertex.
+// Vertices are usize for TSP and GroupVert in other groups for GTSP, the lists are indexed by
+// the vertex index (the global vertex index for GTSP).
+#[derive(Clone)]
+pub struct CandidateLists<V> {
+    pub neighbours: Vec<Vec<V>>
+}
+
+impl<V> CandidateLists<V> {
+    pub fn get(&self, vert: usize) -> &[V] {
+        &self.neighbours[vert]
+    }
+}
+
+// k nearest of the candidates by the distance dist, O(candidates) + O(k log k)
+fn k_nearest<DistFunT: Fn(usize) -> f64>(mut candidates: Vec<usize>, k: usize, dist: DistFunT) -> Vec<usize> {
+    let mut candidates: Vec<(f64, usize)> = candidates.drain(..).map(|u| (dist(u), u)).collect();
+    if candidates.len() > k && k > 0 {
+        candidates.select_nth_unstable_by(k - 1, |a, b| a.0.total_cmp(&b.0));
+    }
+    candidates.truncate(k);
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+    candidates.into_iter().map(|(_, u)| u).collect()
+}
+
+impl CandidateLists<usize> {
+    // O(N^2) scan of the distance matrix, asymmetric distances are taken from the vertex
+    pub fn new<DistanceMatrixT: DistanceMatrix>(distances: &DistanceMatrixT, k: usize) -> Self {
+        let n = distances.get_vert_count();
+        let neighbours = (0..n)
+            .map(|v| k_nearest((0..n).filter(|u| *u != v).collect(), k, |u| distances.get(v, u)))
+            .collect();
+        CandidateLists { neighbours }
+    }
+
+    // uses the nearest neighbours cache if it is large enough
+    pub fn from_coord_distances(distances: &CoordDistances, k: usize) -> Self {
+        let n = distances.get_vert_count();
+        let cache_k = distances.nearest(0).map_or(0, |nearest| nearest.len());
+        if n == 0 || cache_k < k.min(n - 1) {
+            return Self::new(distances, k);
+        }
+        let neighbours = (0..n)
+            .map(|v| distances.nearest(v).unwrap().iter().take(k).map(|(u, _)| *u).collect())
+            .collect();
+        CandidateLists { neighbours }
+    }
+}
+
+impl CandidateLists<GroupVert> {
+    // k nearest vertices in other groups than the group of the vertex
+    pub fn gtsp(problem: &GtspProblem, k: usize) -> Self {
+        let mut vert_group = vec![GroupVert { group: 0, vert: 0 }; problem.vert_count];
+        for (g, group) in problem.groups.iter().enumerate() {
+            for (i, v) in group.iter().enumerate() {
+                vert_group[*v] = GroupVert { group: g, vert: i };
+            }
+        }
+        let neighbours = (0..problem.vert_count)
+            .map(|v| {
+                let others = (0..problem.vert_count).filter(|u| vert_group[*u].group != vert_group[v].group).collect();
+                k_nearest(others, k, |u| problem.distances.get(v, u)).into_iter().map(|u| vert_group[u]).collect()
+            })
+            .collect();
+        CandidateLists { neighbours }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PermMoveType {
+    Move,
+    Swap,
+    Reverse
+}
+
+impl PermMoveType {
+    // move after which the vertex on the position cand_pos follows the vertex on the position pos
+    pub fn connect(&self, pos: usize, cand_pos: usize, len: usize) -> PermMove {
+        let next = (pos + 1) % len;
+        match self {
+            PermMoveType::Move => {
+                if cand_pos > pos {
+                    PermMove::Move { from: cand_pos, to: pos + 1 }
+                } else {
+                    PermMove::Move { from: cand_pos, to: pos }
+                }
+            },
+            PermMoveType::Swap => PermMove::Swap { pos1: next, pos2: cand_pos },
+            PermMoveType::Reverse => PermMove::Reverse { from: next, to: if cand_pos >= next { cand_pos } else { cand_pos + len } }
+        }
+    }
+}
+
+// Move, swap or reversal connecting a random vertex with a random vertex from its candidate list
+#[derive(Clone)]
+pub struct TspNeighbourPerturbation {
+    pub candidates: Rc<CandidateLists<usize>>,
+    pub move_type: PermMoveType
+}
+
+impl PerturbeMutOp<TspPermutation> for TspNeighbourPerturbation {
+    fn eval(&self, data: &mut TspPermutation) {
+        self.eval_moves(data, &mut |_, _| {});
+    }
+}
+
+impl MovePerturbeMutOp<TspPermutation, PermMove> for TspNeighbourPerturbation {
+    fn eval_moves(&self, data: &mut TspPermutation, on_move: &mut dyn FnMut(&TspPermutation, &PermMove)) {
+        let len = data.vert_perm.len();
+        let pos = rand::thread_rng().gen_range(0..len);
+        let candidates = self.candidates.get(data.vert_perm[pos]);
+        if candidates.is_empty() {
+            return;
+        }
+        let cand = candidates[rand::thread_rng().gen_range(0..candidates.len())];
+        // the position lookup is O(N) like applying the move
+        let cand_pos = data.vert_perm.iter().position(|v| *v == cand).unwrap();
+        let mv = self.move_type.connect(pos, cand_pos, len);
+        on_move(data, &mv);
+        apply_perm_move(&mut data.vert_perm, &mv);
+    }
+}
+
+// Connects the groups of a random vertex and a vertex from its candidate list, the candidate
+// vertex is chosen in its group
+#[derive(Clone)]
+pub struct GtspNeighbourPerturbation {
+    pub candidates: Rc<CandidateLists<GroupVert>>,
+    pub move_type: PermMoveType
+}
+
+impl PerturbeMutOp<GtspPermutation> for GtspNeighbourPerturbation {
+    fn eval(&self, data: &mut GtspPermutation) {
+        self.eval_moves(data, &mut |_, _| {});
+    }
+}
+
+impl MovePerturbeMutOp<GtspPermutation, GtspMove> for GtspNeighbourPerturbation {
+    fn eval_moves(&self, data: &mut GtspPermutation, on_move: &mut dyn FnMut(&GtspPermutation, &GtspMove)) {
+        let len = data.perm.len();
+        let pos = rand::thread_rng().gen_range(0..len);
+        let curr = data.perm[pos];
+        let candidates = self.candidates.get(data.spec.groups[curr.group][curr.vert]);
+        if candidates.is_empty() {
+            return;
+        }
+        let cand = candidates[rand::thread_rng().gen_range(0..candidates.len())];
+        let cand_pos = data.perm.iter().position(|gv| gv.group == cand.group).unwrap();
+        let mv = GtspMove::Perm(self.move_type.connect(pos, cand_pos, len));
+        on_move(data, &mv);
+        apply_gtsp_move(data, &mv);
+        let cand_pos = data.perm.iter().position(|gv| gv.group == cand.group).unwrap();
+        if data.perm[cand_pos].vert != cand.vert {
+            let mv = GtspMove::ChangeVert { pos: cand_pos, vert: cand.vert };
+            on_move(data, &mv);
+            apply_gtsp_move(data, &mv);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_connect_moves() {
+        let len = 9;
+        for move_type in [PermMoveType::Move, PermMoveType::Swap, PermMoveType::Reverse] {
+            for pos in 0..len {
+                for cand_pos in 0..len {
+                    if pos == cand_pos {
+                        continue;
+                    }
+                    let mut perm: Vec<usize> = (0..len).collect();
+                    apply_perm_move(&mut perm, &move_type.connect(pos, cand_pos, len));
+                    let new_pos = perm.iter().position(|v| *v == pos).unwrap();
+                    assert_eq!(perm[(new_pos + 1) % len], cand_pos, "{:?} {} {}", move_type, pos, cand_pos);
+                }
+            }
+        }
+    }
+}
@@ -103,4 +103,23 @@ impl<T: OptData> Statistics<T, f64, StochasticRankFitness> for StochasticRankSta
         }
         self.solutions.push(solution);
     }
-}
\ No newline at end of file
+}
+// Static penalty, turns a constrained problem into an unconstrained one
+#[derive(Clone)]
+pub struct PenaltyFunc<T: OptData, FuncT: FitnessFunc<T> + Constraints<T>> {
+    pub func: FuncT,
+    pub weight: f64,
+    _phantom: PhantomData<T>
+}
+
+impl<T: OptData, FuncT: FitnessFunc<T> + Constraints<T>> PenaltyFunc<T, FuncT> {
+    pub fn new(func: FuncT, weight: f64) -> Self {
+        PenaltyFunc { func, weight, _phantom: PhantomData::<T> {} }
+    }
+}
+
+impl<T: OptData, FuncT: FitnessFunc<T> + Constraints<T>> FitnessFunc<T> for PenaltyFunc<T, FuncT> {
+    fn eval(&self, data: &T) -> f64 {
+        self.func.eval(data) + self.weight * self.func.violations_sum(data)
+    }
+}
@@ -0,0 +1,458 @@
+use std::rc::Rc;
+use rand::Rng;
+
+use crate::*;
+
+// Number of the heuristic individuals in the population, at least one if the fraction is positive
+pub fn heuristic_count(size: usize, heuristic_fraction: f64) -> usize {
+    if heuristic_fraction <= 0.0 {
+        return 0;
+    }
+    ((size as f64 * heuristic_fraction).round() as usize).max(1).min(size)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TspConstruction {
+    NearestInsertion,
+    FarthestInsertion,
+    CheapestInsertion,
+    GreedyEdge,
+    // Hilbert curve order of the vertex positions
+    SpaceFillingCurve,
+    // minimum spanning tree with a greedy matching of the odd vertices, needs symmetric distances
+    Christofides
+}
+
+// edge weights of the greedy edge individuals after the first one are randomly increased by up to 10%
+const GREEDY_EDGE_NOISE: f64 = 0.1;
+
+// Population with heuristic_fraction of the individuals built by the construction heuristic
+// and the rest random
+#[derive(Clone)]
+pub struct InitHeuristicTspPopulation<DistanceMatrixT: DistanceMatrix = Distances> {
+    pub distances: Rc<DistanceMatrixT>,
+    // only needed by the space filling curve
+    pub positions: Option<Rc<Vec<[f64; 2]>>>,
+    pub size: usize,
+    pub construction: TspConstruction,
+    pub heuristic_fraction: f64
+}
+
+impl<DistanceMatrixT: DistanceMatrix> InitHeuristicTspPopulation<DistanceMatrixT> {
+    // index of the heuristic individual, used to diversify deterministic heuristics
+    fn gen_perm(&self, index: usize) -> TspPermutation {
+        let distances = self.distances.as_ref();
+        let vert_perm = match self.construction {
+            TspConstruction::NearestInsertion | TspConstruction::FarthestInsertion | TspConstruction::CheapestInsertion =>
+                insertion_tour(distances, self.construction),
+            TspConstruction::GreedyEdge => greedy_edge_tour(distances, if index == 0 { 0.0 } else { GREEDY_EDGE_NOISE }),
+            TspConstruction::SpaceFillingCurve =>
+                space_filling_curve_tour(self.positions.as_ref().expect("space filling curve needs positions"), index % 8),
+            TspConstruction::Christofides => christofides_tour(distances)
+        };
+        TspPermutation { vert_perm }
+    }
+}
+
+impl<DistanceMatrixT: DistanceMatrix + Clone> InitPopulation<TspPermutation> for InitHeuristicTspPopulation<DistanceMatrixT> {
+    fn init(&self) -> Vec<TspPermutation> {
+        let heuristic_count = heuristic_count(self.size, self.heuristic_fraction);
+        let init_rand_population = InitTspPopulation { size: self.size - heuristic_count, vert_count: self.distances.get_vert_count() };
+        let mut population = InitPopulation::init(&init_rand_population);
+        population.reserve(heuristic_count);
+        for i in 0..heuristic_count {
+            population.push(self.gen_perm(i));
+        }
+        population
+    }
+}
+
+impl<DistanceMatrixT: DistanceMatrix + Clone> InitFunc<TspPermutation> for InitHeuristicTspPopulation<DistanceMatrixT> {
+    fn init(&self) -> TspPermutation {
+        self.gen_perm(0)
+    }
+}
+
+const NOT_IN_TOUR: usize = usize::MAX;
+
+fn successors_to_perm(next: &[usize], start: usize) -> Vec<usize> {
+    let mut perm = Vec::<usize>::with_capacity(next.len());
+    let mut v = start;
+    loop {
+        perm.push(v);
+        v = next[v];
+        if v == start {
+            break;
+        }
+    }
+    perm
+}
+
+// cheapest edge a -> next[a] of the tour for inserting the vertex
+fn cheapest_tour_edge<DistanceMatrixT: DistanceMatrix>(distances: &DistanceMatrixT, next: &[usize], start: usize, vert: usize) -> (usize, f64) {
+    let mut best = (start, f64::INFINITY);
+    let mut a = start;
+    loop {
+        let b = next[a];
+        let cost = distances.get(a, vert) + distances.get(vert, b) - distances.get(a, b);
+        if cost < best.1 {
+            best = (a, cost);
+        }
+        a = b;
+        if a == start {
+            break;
+        }
+    }
+    best
+}
+
+// Insertion heuristics starting from a random vertex, the tour is a list of successors.
+// Nearest and farthest insertion are O(N^2), cheapest insertion keeps the best edge of every
+// vertex and recomputes it only when the edge is broken, O(N^2) on typical instances.
+fn insertion_tour<DistanceMatrixT: DistanceMatrix>(distances: &DistanceMatrixT, construction: TspConstruction) -> Vec<usize> {
+    let n = distances.get_vert_count();
+    let start = rand::thread_rng().gen_range(0..n);
+    let mut next = vec![NOT_IN_TOUR; n];
+    next[start] = start;
+    let dist_to = |u: usize, v: usize| distances.get(u, v).min(distances.get(v, u));
+    // distance to the tour for nearest and farthest insertion, insertion cost for cheapest insertion
+    let mut key: Vec<f64> = (0..n).map(|v| dist_to(start, v)).collect();
+    let mut best_edge = vec![start; n];
+    if construction == TspConstruction::CheapestInsertion {
+        for v in 0..n {
+            key[v] = distances.get(start, v) + distances.get(v, start);
+        }
+    }
+    for _ in 1..n {
+        let unvisited = (0..n).filter(|v| next[*v] == NOT_IN_TOUR);
+        let vert = if construction == TspConstruction::FarthestInsertion {
+            unvisited.max_by(|u, v| key[*u].total_cmp(&key[*v])).unwrap()
+        } else {
+            unvisited.min_by(|u, v| key[*u].total_cmp(&key[*v])).unwrap()
+        };
+        let a = if construction == TspConstruction::CheapestInsertion {
+            best_edge[vert]
+        } else {
+            cheapest_tour_edge(distances, &next, start, vert).0
+        };
+        let b = next[a];
+        next[vert] = b;
+        next[a] = vert;
+        for v in 0..n {
+            if next[v] != NOT_IN_TOUR {
+                continue;
+            }
+            if construction == TspConstruction::CheapestInsertion {
+                if best_edge[v] == a {
+                    (best_edge[v], key[v]) = cheapest_tour_edge(distances, &next, start, v);
+                } else {
+                    for (e1, e2) in [(a, vert), (vert, b)] {
+                        let cost = distances.get(e1, v) + distances.get(v, e2) - distances.get(e1, e2);
+                        if cost < key[v] {
+                            best_edge[v] = e1;
+                            key[v] = cost;
+                        }
+                    }
+                }
+            } else {
+                key[v] = key[v].min(dist_to(vert, v));
+            }
+        }
+    }
+    successors_to_perm(&next, start)
+}
+
+fn find_root(parent: &mut [usize], mut v: usize) -> usize {
+    while parent[v] != v {
+        parent[v] = parent[parent[v]];
+        v = parent[v];
+    }
+    v
+}
+
+// Greedy matching of the shortest edges into paths which are joined by nearest neighbour,
+// O(N^2 log N). Asymmetric distances use directed edges.
+fn greedy_edge_tour<DistanceMatrixT: DistanceMatrix>(distances: &DistanceMatrixT, noise: f64) -> Vec<usize> {
+    let n = distances.get_vert_count();
+    let symmetric = distances.is_symmetric();
+    let mut edges = Vec::<(f64, usize, usize)>::new();
+    for u in 0..n {
+        for v in 0..n {
+            if u == v || (symmetric && v < u) {
+                continue;
+            }
+            edges.push((distances.get(u, v) * (1.0 + noise * rand::random::<f64>()), u, v));
+        }
+    }
+    edges.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut parent: Vec<usize> = (0..n).collect();
+    // neighbours for symmetric, predecessor and successor for asymmetric distances
+    let mut adj = vec![[NOT_IN_TOUR; 2]; n];
+    let free = |adj: &[[usize; 2]], v: usize, side: usize| if symmetric { adj[v][1] == NOT_IN_TOUR } else { adj[v][side] == NOT_IN_TOUR };
+    let mut added = 0;
+    for (_, u, v) in edges {
+        if added + 1 >= n {
+            break;
+        }
+        if !free(&adj, u, 1) || !free(&adj, v, 0) {
+            continue;
+        }
+        let (ru, rv) = (find_root(&mut parent, u), find_root(&mut parent, v));
+        if ru == rv {
+            continue;
+        }
+        parent[ru] = rv;
+        if symmetric {
+            let su = if adj[u][0] == NOT_IN_TOUR { 0 } else { 1 };
+            let sv = if adj[v][0] == NOT_IN_TOUR { 0 } else { 1 };
+            adj[u][su] = v;
+            adj[v][sv] = u;
+        } else {
+            adj[u][1] = v;
+            adj[v][0] = u;
+        }
+        added += 1;
+    }
+    // paths starting in vertices with a free predecessor
+    let mut fragments = Vec::<Vec<usize>>::new();
+    let mut visited = vec![false; n];
+    for v in 0..n {
+        let is_start = if symmetric { adj[v][1] == NOT_IN_TOUR } else { adj[v][0] == NOT_IN_TOUR };
+        if visited[v] || !is_start {
+            continue;
+        }
+        let mut fragment = vec![v];
+        visited[v] = true;
+        let mut curr = v;
+        loop {
+            let succ = if symmetric {
+                adj[curr].iter().copied().find(|u| *u != NOT_IN_TOUR && !visited[*u])
+            } else {
+                Some(adj[curr][1]).filter(|u| *u != NOT_IN_TOUR)
+            };
+            match succ {
+                Some(u) => {
+                    visited[u] = true;
+                    fragment.push(u);
+                    curr = u;
+                },
+                None => break
+            }
+        }
+        fragments.push(fragment);
+    }
+    let mut tour = fragments.swap_remove(0);
+    while !fragments.is_empty() {
+        let last = *tour.last().unwrap();
+        let mut best = (0, false, f64::INFINITY);
+        for (i, fragment) in fragments.iter().enumerate() {
+            let dist = distances.get(last, fragment[0]);
+            if dist < best.2 {
+                best = (i, false, dist);
+            }
+            let dist = distances.get(last, *fragment.last().unwrap());
+            if symmetric && dist < best.2 {
+                best = (i, true, dist);
+            }
+        }
+        let mut fragment = fragments.swap_remove(best.0);
+        if best.1 {
+            fragment.reverse();
+        }
+        tour.append(&mut fragment);
+    }
+    tour
+}
+
+// index of the cell on the Hilbert curve filling the 2^bits x 2^bits grid
+fn hilbert_index(bits: u32, mut x: u64, mut y: u64) -> u64 {
+    let n = 1u64 << bits;
+    let mut index = 0;
+    let mut s = n / 2;
+    while s > 0 {
+        let rx = ((x & s) > 0) as u64;
+        let ry = ((y & s) > 0) as u64;
+        index += s * s * ((3 * rx) ^ ry);
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    index
+}
+
+// Vertices sorted by the Hilbert curve index, the orientation 0..8 flips and transposes the curve
+fn space_filling_curve_tour(positions: &[[f64; 2]], orientation: usize) -> Vec<usize> {
+    const BITS: u32 = 16;
+    let mut min = [f64::INFINITY; 2];
+    let mut max = [f64::NEG_INFINITY; 2];
+    for pos in positions {
+        for d in 0..2 {
+            min[d] = min[d].min(pos[d]);
+            max[d] = max[d].max(pos[d]);
+        }
+    }
+    let size = (max[0] - min[0]).max(max[1] - min[1]).max(f64::MIN_POSITIVE);
+    let cells = ((1u64 << BITS) - 1) as f64;
+    let mut keys: Vec<(u64, usize)> = positions.iter().enumerate().map(|(v, pos)| {
+        let mut coords = [0, 1].map(|d| ((pos[d] - min[d]) / size * cells) as u64);
+        for d in 0..2 {
+            if orientation & (1 << d) != 0 {
+                coords[d] = cells as u64 - coords[d];
+            }
+        }
+        if orientation & 4 != 0 {
+            coords.swap(0, 1);
+        }
+        (hilbert_index(BITS, coords[0], coords[1]), v)
+    }).collect();
+    keys.sort();
+    keys.into_iter().map(|(_, v)| v).collect()
+}
+
+// Minimum spanning tree plus a greedy (instead of minimum weight perfect) matching of the odd
+// degree vertices, the Euler tour from a random vertex is shortcut to a tour, O(N^2)
+fn christofides_tour<DistanceMatrixT: DistanceMatrix>(distances: &DistanceMatrixT) -> Vec<usize> {
+    if !distances.is_symmetric() {
+        panic!("christofides construction needs symmetric distances");
+    }
+    let n = distances.get_vert_count();
+    let mut edges = Vec::<(usize, usize)>::with_capacity(2 * n);
+    // Prim
+    let mut in_tree = vec![false; n];
+    let mut key = vec![f64::INFINITY; n];
+    let mut parent = vec![NOT_IN_TOUR; n];
+    key[0] = 0.0;
+    for _ in 0..n {
+        let v = (0..n).filter(|v| !in_tree[*v]).min_by(|u, v| key[*u].total_cmp(&key[*v])).unwrap();
+        in_tree[v] = true;
+        if parent[v] != NOT_IN_TOUR {
+            edges.push((parent[v], v));
+        }
+        for u in 0..n {
+            let dist = distances.get(v, u);
+            if !in_tree[u] && dist < key[u] {
+                key[u] = dist;
+                parent[u] = v;
+            }
+        }
+    }
+    let mut degree = vec![0usize; n];
+    for (u, v) in &edges {
+        degree[*u] += 1;
+        degree[*v] += 1;
+    }
+    let odd: Vec<usize> = (0..n).filter(|v| degree[*v] % 2 == 1).collect();
+    let mut pairs = Vec::<(f64, usize, usize)>::with_capacity(odd.len() * odd.len() / 2);
+    for i in 0..odd.len() {
+        for j in (i + 1)..odd.len() {
+            pairs.push((distances.get(odd[i], odd[j]), odd[i], odd[j]));
+        }
+    }
+    pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut matched = vec![false; n];
+    for (_, u, v) in pairs {
+        if !matched[u] && !matched[v] {
+            matched[u] = true;
+            matched[v] = true;
+            edges.push((u, v));
+        }
+    }
+    // Hierholzer
+    let mut adj = vec![Vec::<usize>::new(); n];
+    for (e, (u, v)) in edges.iter().enumerate() {
+        adj[*u].push(e);
+        adj[*v].push(e);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut adj_pos = vec![0usize; n];
+    let mut stack = vec![rand::thread_rng().gen_range(0..n)];
+    let mut circuit = Vec::<usize>::with_capacity(edges.len() + 1);
+    while let Some(&v) = stack.last() {
+        while adj_pos[v] < adj[v].len() && used[adj[v][adj_pos[v]]] {
+            adj_pos[v] += 1;
+        }
+        if adj_pos[v] < adj[v].len() {
+            let e = adj[v][adj_pos[v]];
+            used[e] = true;
+            stack.push(if edges[e].0 == v { edges[e].1 } else { edges[e].0 });
+        } else {
+            circuit.push(v);
+            stack.pop();
+        }
+    }
+    let mut visited = vec![false; n];
+    circuit.into_iter().filter(|v| !std::mem::replace(&mut visited[*v], true)).collect()
+}
+
+// Cheapest insertion choosing the group, its vertex and the insertion position jointly, O(M^2 N)
+pub fn gtsp_cheapest_insertion(spec: &GtspProblem) -> Vec<GroupVert> {
+    let dist = |u: &GroupVert, v: &GroupVert| spec.distances.get(spec.groups[u.group][u.vert], spec.groups[v.group][v.vert]);
+    let mut perm = Vec::<GroupVert>::with_capacity(spec.groups.len());
+    let mut group_visited = vec![false; spec.groups.len()];
+    let start_group = rand::thread_rng().gen_range(0..spec.groups.len());
+    perm.push(GroupVert { group: start_group, vert: rand::thread_rng().gen_range(0..spec.groups[start_group].len()) });
+    group_visited[start_group] = true;
+    while perm.len() < spec.groups.len() {
+        let mut best = (GroupVert { group: 0, vert: 0 }, 0, f64::INFINITY);
+        for g in 0..spec.groups.len() {
+            if group_visited[g] {
+                continue;
+            }
+            for v in 0..spec.groups[g].len() {
+                let gv = GroupVert { group: g, vert: v };
+                for i in 0..perm.len() {
+                    let a = &perm[i];
+                    let b = &perm[(i + 1) % perm.len()];
+                    let cost = dist(a, &gv) + dist(&gv, b) - dist(a, b);
+                    if cost < best.2 {
+                        best = (gv, i + 1, cost);
+                    }
+                }
+            }
+        }
+        group_visited[best.0.group] = true;
+        perm.insert(best.1, best.0);
+    }
+    perm
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_constructions() {
+        let problem = load_tsplib("data/tsp/berlin52.tsp");
+        let distances = Rc::new(problem.distances());
+        let fitness = TspFitness { distances: distances.as_ref().clone() };
+        let opt = 7542.0;
+        let random_len = fitness.eval(&InitFunc::init(&InitTspPopulation { size: 1, vert_count: 52 }));
+        for construction in [
+            TspConstruction::NearestInsertion, TspConstruction::FarthestInsertion, TspConstruction::CheapestInsertion,
+            TspConstruction::GreedyEdge, TspConstruction::SpaceFillingCurve, TspConstruction::Christofides
+        ] {
+            let init = InitHeuristicTspPopulation {
+                distances: distances.clone(),
+                positions: Some(Rc::new(problem.positions.clone())),
+                size: 8,
+                construction,
+                heuristic_fraction: 0.5
+            };
+            let population = InitPopulation::init(&init);
+            assert_eq!(population.len(), 8);
+            for perm in &population[4..] {
+                let mut sorted = perm.vert_perm.clone();
+                sorted.sort();
+                assert_eq!(sorted, (0..52).collect::<Vec<usize>>());
+                let len = fitness.eval(perm);
+                assert!(len >= opt && len < 1.6 * opt && len < random_len, "{:?} {}", construction, len);
+            }
+        }
+    }
+}
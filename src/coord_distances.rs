@@ -0,0 +1,164 @@
+use crate::tsp::*;
+use crate::tsp_utils::*;
+
+// Distances computed on demand from the vertex coordinates, memory is O(N) instead of O(N^2).
+// The optional cache keeps the k nearest neighbours of every vertex with their distances.
+#[derive(Clone)]
+pub struct CoordDistances {
+    pub positions: Vec<[f64; 2]>,
+    pub edge_weight_type: TsplibEdgeWeightType,
+    // neighbours sorted by the distance
+    knn_cache: Option<Vec<Vec<(usize, f64)>>>
+}
+
+impl CoordDistances {
+    pub fn new(positions: Vec<[f64; 2]>, edge_weight_type: TsplibEdgeWeightType) -> Self {
+        if edge_weight_type == TsplibEdgeWeightType::Explicit {
+            panic!("explicit weights have no coordinates");
+        }
+        CoordDistances { positions, edge_weight_type, knn_cache: None }
+    }
+
+    pub fn with_knn_cache(positions: Vec<[f64; 2]>, edge_weight_type: TsplibEdgeWeightType, k: usize) -> Self {
+        let mut distances = Self::new(positions, edge_weight_type);
+        let knn = if edge_weight_type == TsplibEdgeWeightType::Geo {
+            distances.knn_brute_force(k)
+        } else {
+            distances.knn_grid(k)
+        };
+        distances.knn_cache = Some(knn);
+        distances
+    }
+
+    // cached nearest neighbours of the vertex, None without the cache
+    pub fn nearest(&self, vert: usize) -> Option<&[(usize, f64)]> {
+        self.knn_cache.as_ref().map(|knn| knn[vert].as_slice())
+    }
+
+    fn compute(&self, v1: usize, v2: usize) -> f64 {
+        if v1 == v2 {
+            return 0.0;
+        }
+        tsplib_distance(&self.positions[v1], &self.positions[v2], self.edge_weight_type)
+    }
+
+    fn sort_neighbours(&self, vert: usize, candidates: Vec<usize>, k: usize) -> Vec<(usize, f64)> {
+        let mut neighbours: Vec<(usize, f64)> = candidates.into_iter().map(|u| (u, self.compute(vert, u))).collect();
+        neighbours.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        neighbours.truncate(k);
+        neighbours
+    }
+
+    fn knn_brute_force(&self, k: usize) -> Vec<Vec<(usize, f64)>> {
+        let n = self.positions.len();
+        (0..n).map(|v| self.sort_neighbours(v, (0..n).filter(|u| *u != v).collect(), k)).collect()
+    }
+
+    // planar metrics grow with the euclidean distance, so the neighbours are searched in rings
+    // of grid cells around the vertex until the next ring can't contain a closer vertex
+    fn knn_grid(&self, k: usize) -> Vec<Vec<(usize, f64)>> {
+        let n = self.positions.len();
+        let k = k.min(n.saturating_sub(1));
+        let mut min = [f64::INFINITY; 2];
+        let mut max = [f64::NEG_INFINITY; 2];
+        for pos in &self.positions {
+            for d in 0..2 {
+                min[d] = min[d].min(pos[d]);
+                max[d] = max[d].max(pos[d]);
+            }
+        }
+        let cells_per_dim = ((n as f64 / 2.0).sqrt().ceil() as usize).max(1);
+        let cell_size = ((max[0] - min[0]).max(max[1] - min[1]) / cells_per_dim as f64).max(f64::MIN_POSITIVE);
+        let cell_of = |pos: &[f64; 2]| -> [usize; 2] {
+            [0, 1].map(|d| (((pos[d] - min[d]) / cell_size) as usize).min(cells_per_dim - 1))
+        };
+        let mut cells = vec![Vec::<usize>::new(); cells_per_dim * cells_per_dim];
+        for v in 0..n {
+            let cell = cell_of(&self.positions[v]);
+            cells[cell[0] * cells_per_dim + cell[1]].push(v);
+        }
+        let euclidean2 = |v1: usize, v2: usize| {
+            let dx = self.positions[v1][0] - self.positions[v2][0];
+            let dy = self.positions[v1][1] - self.positions[v2][1];
+            dx * dx + dy * dy
+        };
+        let mut knn = Vec::with_capacity(n);
+        for v in 0..n {
+            let cell = cell_of(&self.positions[v]);
+            let mut nearest: Vec<(f64, usize)> = Vec::new();
+            for ring in 0..cells_per_dim {
+                let (x0, x1) = (cell[0] as i64 - ring as i64, cell[0] as i64 + ring as i64);
+                let (y0, y1) = (cell[1] as i64 - ring as i64, cell[1] as i64 + ring as i64);
+                for x in x0..=x1 {
+                    for y in y0..=y1 {
+                        let on_ring = x == x0 || x == x1 || y == y0 || y == y1;
+                        if !on_ring || x < 0 || y < 0 || x >= cells_per_dim as i64 || y >= cells_per_dim as i64 {
+                            continue;
+                        }
+                        for u in &cells[x as usize * cells_per_dim + y as usize] {
+                            if *u != v {
+                                nearest.push((euclidean2(v, *u), *u));
+                            }
+                        }
+                    }
+                }
+                nearest.sort_by(|a, b| a.0.total_cmp(&b.0));
+                nearest.truncate(k);
+                let ring_dist = ring as f64 * cell_size;
+                if nearest.len() == k && nearest.last().is_none_or(|last| last.0 <= ring_dist * ring_dist) {
+                    break;
+                }
+            }
+            knn.push(self.sort_neighbours(v, nearest.into_iter().map(|(_, u)| u).collect(), k));
+        }
+        knn
+    }
+}
+
+impl DistanceMatrix for CoordDistances {
+    fn get_vert_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    // the cache is only used by nearest, searching it is slower than computing the distance
+    fn get(&self, v1: usize, v2: usize) -> f64 {
+        self.compute(v1, v2)
+    }
+
+    fn is_symmetric(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_knn_matches_matrix() {
+        let positions: Vec<[f64; 2]> = (0..300).map(|_| [rand::random::<f64>() * 1000.0, rand::random::<f64>() * 100.0]).collect();
+        let k = 8;
+        let lazy = CoordDistances::with_knn_cache(positions.clone(), TsplibEdgeWeightType::Euc2d, k);
+        let brute = CoordDistances::new(positions.clone(), TsplibEdgeWeightType::Euc2d).knn_brute_force(k);
+        let matrix = TsplibProblem {
+            name: String::new(),
+            problem_type: String::from("TSP"),
+            dimension: positions.len(),
+            edge_weight_type: TsplibEdgeWeightType::Euc2d,
+            edge_weight_format: None,
+            positions,
+            edge_weights: Vec::new()
+        }.distances();
+        for v in 0..300 {
+            let nearest = lazy.nearest(v).unwrap();
+            // rounded distances may tie, so only the distances have to match
+            for i in 0..k {
+                assert_eq!(nearest[i].1, brute[v][i].1);
+            }
+            for u in 0..300 {
+                assert_eq!(lazy.get(v, u), matrix.get(v, u));
+            }
+        }
+    }
+}
@@ -1,6 +1,11 @@
+use std::rc::Rc;
+
 use crate::opt_traits::*;
 use crate::opt_data::*;
+use crate::bound_handling::*;
+use crate::bit_array::*;
 use rand::Rng;
+use rand_distr::{Normal, Distribution};
 
 pub struct IdentityCrossover {
 
@@ -87,4 +92,711 @@ impl Crossover<FloatVec> for ArithmetricCrossover {
     fn crossover(&self, population: &Vec<FloatVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<FloatVec>) {
         crossover_vec_data(population, parents_indices, offsprings, self);
     }
-}
\ No newline at end of file
+}
+pub struct UniformCrossover {
+}
+
+impl<T: Clone> CrossoverFun<T> for UniformCrossover {
+    fn crossover_fun(&self, parents: [&Vec<T>; 2], offsprings: [&mut Vec<T>; 2]) {
+        for i in 0..parents[0].len() {
+            let swap = rand::random::<bool>();
+            for o in 0..2 {
+                let offspring_parent = if swap { 1 - o } else { o };
+                offsprings[o].push(parents[offspring_parent][i].clone());
+            }
+        }
+    }
+}
+
+impl Crossover<FloatVec> for UniformCrossover {
+    fn crossover(&self, population: &Vec<FloatVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<FloatVec>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
+impl Crossover<NaiveBitVec> for UniformCrossover {
+    fn crossover(&self, population: &Vec<NaiveBitVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<NaiveBitVec>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
+pub struct KPointCrossover {
+    pub k: usize
+}
+
+// sorted distinct split points in 1..len, each one switches the parent the offspring copies from
+pub fn gen_split_points(len: usize, k: usize) -> Vec<usize> {
+    if len < 2 {
+        return Vec::new();
+    }
+    let k = k.min(len - 1);
+    let mut points = rand::seq::index::sample(&mut rand::thread_rng(), len - 1, k).into_vec();
+    for point in &mut points {
+        *point += 1;
+    }
+    points.sort();
+    points
+}
+
+impl<T: Clone> CrossoverFun<T> for KPointCrossover {
+    fn crossover_fun(&self, parents: [&Vec<T>; 2], offsprings: [&mut Vec<T>; 2]) {
+        let split_points = gen_split_points(parents[0].len(), self.k);
+        let mut next_split = 0;
+        let mut swap = false;
+        for i in 0..parents[0].len() {
+            if next_split < split_points.len() && split_points[next_split] == i {
+                swap = !swap;
+                next_split += 1;
+            }
+            for o in 0..2 {
+                let offspring_parent = if swap { 1 - o } else { o };
+                offsprings[o].push(parents[offspring_parent][i].clone());
+            }
+        }
+    }
+}
+
+impl Crossover<FloatVec> for KPointCrossover {
+    fn crossover(&self, population: &Vec<FloatVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<FloatVec>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
+impl Crossover<NaiveBitVec> for KPointCrossover {
+    fn crossover(&self, population: &Vec<NaiveBitVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<NaiveBitVec>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
+impl Crossover<IntVec> for OnePointCrossover {
+    fn crossover(&self, population: &Vec<IntVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<IntVec>) {
+        crossover_vec_data(population, parents_indices, offsprings, &OnePointCrossoverFun{});
+    }
+}
+
+impl Crossover<IntVec> for UniformCrossover {
+    fn crossover(&self, population: &Vec<IntVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<IntVec>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
+impl Crossover<IntVec> for KPointCrossover {
+    fn crossover(&self, population: &Vec<IntVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<IntVec>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
+// Arithmetic crossover of integers, the weighted mean is rounded and stays between the parents
+pub struct IntArithmeticCrossover {
+}
+
+impl CrossoverFun<i64> for IntArithmeticCrossover {
+    fn crossover_fun(&self, parents: [&Vec<i64>; 2], offsprings: [&mut Vec<i64>; 2]) {
+        for o in 0..2 {
+            let c = rand::random::<f64>();
+            for i in 0..parents[0].len() {
+                let val = c * parents[0][i] as f64 + (1.0 - c) * parents[1][i] as f64;
+                offsprings[o].push(val.round() as i64);
+            }
+        }
+    }
+}
+
+impl Crossover<IntVec> for IntArithmeticCrossover {
+    fn crossover(&self, population: &Vec<IntVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<IntVec>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
+// Crossover of packed bits, the mask selects the bits exchanged between the parents
+pub fn crossover_bit_vec<MaskFunT: Fn(usize) -> Vec<u64>>
+    (population: &Vec<BitVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<BitVec>, gen_mask: &MaskFunT)
+{
+    offsprings.clear();
+    for i in (0..parents_indices.len()).step_by(2) {
+        if i + 1 >= parents_indices.len() {
+            continue;
+        }
+        let parent1 = &population[parents_indices[i]];
+        let parent2 = &population[parents_indices[i + 1]];
+        let mask = gen_mask(parent1.len());
+        let [offspring1, offspring2] = BitVec::crossover_with_mask([parent1, parent2], &mask);
+        offsprings.push(offspring1);
+        offsprings.push(offspring2);
+    }
+}
+
+impl Crossover<BitVec> for OnePointCrossover {
+    fn crossover(&self, population: &Vec<BitVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<BitVec>) {
+        crossover_bit_vec(population, parents_indices, offsprings, &|bit_count| k_point_mask(bit_count, 1));
+    }
+}
+
+impl Crossover<BitVec> for UniformCrossover {
+    fn crossover(&self, population: &Vec<BitVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<BitVec>) {
+        crossover_bit_vec(population, parents_indices, offsprings, &|bit_count| {
+            (0..blocks_count(bit_count)).map(|_| rand::random::<u64>()).collect()
+        });
+    }
+}
+
+// bits between every odd and even split point are set
+pub fn k_point_mask(bit_count: usize, k: usize) -> Vec<u64> {
+    let mut mask = vec![0u64; blocks_count(bit_count)];
+    let mut split_points = gen_split_points(bit_count, k);
+    if split_points.len() % 2 == 1 {
+        split_points.push(bit_count);
+    }
+    for s in (0..split_points.len()).step_by(2) {
+        set_bit_range(&mut mask, split_points[s], split_points[s + 1]);
+    }
+    mask
+}
+
+impl Crossover<BitVec> for KPointCrossover {
+    fn crossover(&self, population: &Vec<BitVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<BitVec>) {
+        crossover_bit_vec(population, parents_indices, offsprings, &|bit_count| k_point_mask(bit_count, self.k));
+    }
+}
+
+// Deb's simulated binary crossover, with bounds it uses the bounded variant from the NSGA-II implementation
+pub struct SimulatedBinaryCrossover {
+    // distribution index, larger values create offsprings closer to the parents
+    pub eta: f64,
+    // probability of crossing each variable
+    pub var_prob: f64,
+    pub bounds: Vec<Bounds>
+}
+
+impl SimulatedBinaryCrossover {
+    pub fn new(eta: f64, bounds: &Vec<Bounds>) -> Self {
+        SimulatedBinaryCrossover { eta, var_prob: 0.5, bounds: bounds.clone() }
+    }
+
+    fn beta_q(&self, alpha: f64, u: f64) -> f64 {
+        let exponent = 1.0 / (self.eta + 1.0);
+        if u <= 1.0 / alpha {
+            (u * alpha).powf(exponent)
+        } else {
+            (1.0 / (2.0 - u * alpha)).powf(exponent)
+        }
+    }
+}
+
+impl CrossoverFun<f64> for SimulatedBinaryCrossover {
+    fn crossover_fun(&self, parents: [&Vec<f64>; 2], offsprings: [&mut Vec<f64>; 2]) {
+        for i in 0..parents[0].len() {
+            let y1 = parents[0][i].min(parents[1][i]);
+            let y2 = parents[0][i].max(parents[1][i]);
+            if rand::random::<f64>() > self.var_prob || y2 - y1 < 1.0e-14 {
+                for o in 0..2 {
+                    offsprings[o].push(parents[o][i]);
+                }
+                continue;
+            }
+            let (lower, upper) = if i < self.bounds.len() {
+                (self.bounds[i].lower, self.bounds[i].upper)
+            } else {
+                (f64::NEG_INFINITY, f64::INFINITY)
+            };
+            let u = rand::random::<f64>();
+            let beta_lower = 1.0 + 2.0 * (y1 - lower) / (y2 - y1);
+            let beta_upper = 1.0 + 2.0 * (upper - y2) / (y2 - y1);
+            // with infinite bounds alpha goes to 2, the unbounded SBX
+            let alpha_lower = 2.0 - beta_lower.powf(-(self.eta + 1.0));
+            let alpha_upper = 2.0 - beta_upper.powf(-(self.eta + 1.0));
+            let mut c1 = 0.5 * ((y1 + y2) - self.beta_q(alpha_lower, u) * (y2 - y1));
+            let mut c2 = 0.5 * ((y1 + y2) + self.beta_q(alpha_upper, u) * (y2 - y1));
+            c1 = c1.clamp(lower, upper);
+            c2 = c2.clamp(lower, upper);
+            if rand::random::<bool>() {
+                std::mem::swap(&mut c1, &mut c2);
+            }
+            offsprings[0].push(c1);
+            offsprings[1].push(c2);
+        }
+    }
+}
+
+impl Crossover<FloatVec> for SimulatedBinaryCrossover {
+    fn crossover(&self, population: &Vec<FloatVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<FloatVec>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
+// BLX-alpha, samples uniformly from the parents interval extended by alpha times its length on both sides
+pub struct BlendCrossover {
+    pub alpha: f64
+}
+
+impl CrossoverFun<f64> for BlendCrossover {
+    fn crossover_fun(&self, parents: [&Vec<f64>; 2], offsprings: [&mut Vec<f64>; 2]) {
+        for i in 0..parents[0].len() {
+            let c_min = parents[0][i].min(parents[1][i]);
+            let c_max = parents[0][i].max(parents[1][i]);
+            let extension = self.alpha * (c_max - c_min);
+            for o in 0..2 {
+                let r = rand::random::<f64>();
+                offsprings[o].push(c_min - extension + r * (c_max - c_min + 2.0 * extension));
+            }
+        }
+    }
+}
+
+impl Crossover<FloatVec> for BlendCrossover {
+    fn crossover(&self, population: &Vec<FloatVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<FloatVec>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
+// orders the parents from the better to the worse one
+fn order_parents_by_fitness<'a>(fitness_func: &Rc<dyn FitnessFunc<FloatVec>>, parents: [&'a Vec<f64>; 2]) -> [&'a Vec<f64>; 2] {
+    let fitness1 = fitness_func.eval(&FloatVec { values: parents[0].clone() });
+    let fitness2 = fitness_func.eval(&FloatVec { values: parents[1].clone() });
+    if fitness1 <= fitness2 { parents } else { [parents[1], parents[0]] }
+}
+
+// Wright's heuristic crossover, offsprings are placed beyond the better parent in the direction away from the worse one
+#[derive(Clone)]
+pub struct WrightHeuristicCrossover {
+    pub fitness_func: Rc<dyn FitnessFunc<FloatVec>>
+}
+
+impl CrossoverFun<f64> for WrightHeuristicCrossover {
+    fn crossover_fun(&self, parents: [&Vec<f64>; 2], offsprings: [&mut Vec<f64>; 2]) {
+        let [better, worse] = order_parents_by_fitness(&self.fitness_func, parents);
+        for o in 0..2 {
+            let r = rand::random::<f64>();
+            for i in 0..better.len() {
+                offsprings[o].push(better[i] + r * (better[i] - worse[i]));
+            }
+        }
+    }
+}
+
+impl Crossover<FloatVec> for WrightHeuristicCrossover {
+    fn crossover(&self, population: &Vec<FloatVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<FloatVec>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
+// Wright's linear crossover, creates the midpoint and two extrapolations and keeps the best two
+#[derive(Clone)]
+pub struct LinearCrossover {
+    pub fitness_func: Rc<dyn FitnessFunc<FloatVec>>
+}
+
+impl CrossoverFun<f64> for LinearCrossover {
+    fn crossover_fun(&self, parents: [&Vec<f64>; 2], offsprings: [&mut Vec<f64>; 2]) {
+        let coefs = [(0.5, 0.5), (1.5, -0.5), (-0.5, 1.5)];
+        let mut candidates = Vec::<(f64, FloatVec)>::with_capacity(coefs.len());
+        for (c1, c2) in coefs {
+            let candidate = FloatVec { values: (0..parents[0].len()).map(|i| c1 * parents[0][i] + c2 * parents[1][i]).collect() };
+            candidates.push((self.fitness_func.eval(&candidate), candidate));
+        }
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for o in 0..2 {
+            offsprings[o].extend_from_slice(&candidates[o].1.values);
+        }
+    }
+}
+
+impl Crossover<FloatVec> for LinearCrossover {
+    fn crossover(&self, population: &Vec<FloatVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<FloatVec>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
+// Applies bound handling to the offsprings of any FloatVec crossover,
+// offspring k is repaired towards the parent it was created at the position of
+pub struct BoundedCrossover<CrossoverT: Crossover<FloatVec>> {
+    pub crossover: CrossoverT,
+    pub bounds: Vec<Bounds>,
+    pub bound_handling: BoundHandling
+}
+
+impl<CrossoverT: Crossover<FloatVec>> Crossover<FloatVec> for BoundedCrossover<CrossoverT> {
+    fn crossover(&self, population: &Vec<FloatVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<FloatVec>) {
+        self.crossover.crossover(population, parents_indices, offsprings);
+        for k in 0..offsprings.len() {
+            let parent = &population[parents_indices[k.min(parents_indices.len() - 1)]];
+            handle_bounds(&mut offsprings[k].values, &parent.values, &self.bounds, self.bound_handling);
+        }
+    }
+}
+
+// Crossover of any arity, creates offsprings_count offsprings from parents_count parents
+pub trait MultiCrossoverFun<T: Clone> {
+    fn parents_count(&self) -> usize;
+    fn offsprings_count(&self) -> usize;
+    // offsprings are empty vectors which should be filled
+    fn multi_crossover_fun(&self, parents: &[&Vec<T>], offsprings: &mut [Vec<T>]);
+}
+
+// Adapter of the 2 -> 2 crossovers for the multi parent API
+pub struct PairCrossover<CrossoverFunT> {
+    pub crossover_fun: CrossoverFunT
+}
+
+impl<T: Clone, CrossoverFunT: CrossoverFun<T>> MultiCrossoverFun<T> for PairCrossover<CrossoverFunT> {
+    fn parents_count(&self) -> usize {
+        2
+    }
+    fn offsprings_count(&self) -> usize {
+        2
+    }
+    fn multi_crossover_fun(&self, parents: &[&Vec<T>], offsprings: &mut [Vec<T>]) {
+        let [offspring1, offspring2] = offsprings else { panic!("pair crossover creates 2 offsprings") };
+        self.crossover_fun.crossover_fun([parents[0], parents[1]], [offspring1, offspring2]);
+    }
+}
+
+// Every group of parents starts offsprings_count positions after the previous one and wraps around,
+// so the number of offsprings equals the number of selected parents. For 2 -> 2 operators and an even
+// number of parents the groups are the consecutive pairs used by crossover_vec_data, an odd last parent
+// is paired with the first one instead of being skipped. Groups which are not crossed are copied.
+pub fn multi_crossover_vec<T: Clone, MultiCrossoverFunT: MultiCrossoverFun<T> + ?Sized>(
+    parents: &Vec<&Vec<T>>, crossover_fun: &MultiCrossoverFunT, crossover_prob: f64) -> Vec<Vec<T>>
+{
+    let parents_count = crossover_fun.parents_count();
+    let offsprings_count = crossover_fun.offsprings_count();
+    let mut offsprings = Vec::<Vec<T>>::with_capacity(parents.len() + offsprings_count);
+    if parents.is_empty() {
+        return offsprings;
+    }
+    let mut start = 0;
+    while offsprings.len() < parents.len() {
+        let group: Vec<&Vec<T>> = (0..parents_count).map(|j| parents[(start + j) % parents.len()]).collect();
+        if rand::random::<f64>() < crossover_prob {
+            let mut group_offsprings = vec![Vec::<T>::with_capacity(group[0].len()); offsprings_count];
+            crossover_fun.multi_crossover_fun(&group, &mut group_offsprings);
+            offsprings.append(&mut group_offsprings);
+        } else {
+            for o in 0..offsprings_count {
+                offsprings.push(group[o % parents_count].clone());
+            }
+        }
+        start += offsprings_count;
+    }
+    offsprings.truncate(parents.len());
+    offsprings
+}
+
+pub fn multi_crossover_vec_data<T : Clone, VecOptDataT : VecOptData<T>, MultiCrossoverFunT : MultiCrossoverFun<T>>
+    (population: &Vec<VecOptDataT>, parents_indices: &Vec<usize>, offsprings: &mut Vec<VecOptDataT>, crossover_fun: &MultiCrossoverFunT, crossover_prob: f64)
+{
+    offsprings.clear();
+    let parents: Vec<&Vec<T>> = parents_indices.iter().map(|i| population[*i].get()).collect();
+    for values in multi_crossover_vec(&parents, crossover_fun, crossover_prob) {
+        let mut offspring = VecOptDataT::new();
+        *offspring.get_mut() = values;
+        offsprings.push(offspring);
+    }
+}
+
+pub struct MultiCrossover<MultiCrossoverFunT> {
+    pub crossover_fun: MultiCrossoverFunT,
+    pub crossover_prob: f64
+}
+
+impl<MultiCrossoverFunT: MultiCrossoverFun<f64>> Crossover<FloatVec> for MultiCrossover<MultiCrossoverFunT> {
+    fn crossover(&self, population: &Vec<FloatVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<FloatVec>) {
+        multi_crossover_vec_data(population, parents_indices, offsprings, &self.crossover_fun, self.crossover_prob);
+    }
+}
+
+impl<MultiCrossoverFunT: MultiCrossoverFun<u8>> Crossover<NaiveBitVec> for MultiCrossover<MultiCrossoverFunT> {
+    fn crossover(&self, population: &Vec<NaiveBitVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<NaiveBitVec>) {
+        multi_crossover_vec_data(population, parents_indices, offsprings, &self.crossover_fun, self.crossover_prob);
+    }
+}
+
+// Eiben's diagonal crossover, n parents are cut at the same n - 1 points
+// and offspring k takes segment s from parent (k + s) mod n
+pub struct DiagonalCrossover {
+    pub parents_count: usize
+}
+
+impl<T: Clone> MultiCrossoverFun<T> for DiagonalCrossover {
+    fn parents_count(&self) -> usize {
+        self.parents_count
+    }
+    fn offsprings_count(&self) -> usize {
+        self.parents_count
+    }
+    fn multi_crossover_fun(&self, parents: &[&Vec<T>], offsprings: &mut [Vec<T>]) {
+        let n = parents.len();
+        let split_points = gen_split_points(parents[0].len(), n - 1);
+        let mut segment = 0;
+        for i in 0..parents[0].len() {
+            if segment < split_points.len() && split_points[segment] == i {
+                segment += 1;
+            }
+            for o in 0..n {
+                offsprings[o].push(parents[(o + segment) % n][i].clone());
+            }
+        }
+    }
+}
+
+// DE/rand/1/bin, the first parent is the target and the other three create the mutant vector
+pub struct DifferentialCrossover {
+    // differential weight
+    pub f: f64,
+    // probability of taking a variable from the mutant vector
+    pub cr: f64
+}
+
+impl MultiCrossoverFun<f64> for DifferentialCrossover {
+    fn parents_count(&self) -> usize {
+        4
+    }
+    fn offsprings_count(&self) -> usize {
+        1
+    }
+    fn multi_crossover_fun(&self, parents: &[&Vec<f64>], offsprings: &mut [Vec<f64>]) {
+        let target = parents[0];
+        let forced = rand::thread_rng().gen_range(0..target.len());
+        for i in 0..target.len() {
+            if i == forced || rand::random::<f64>() < self.cr {
+                offsprings[0].push(parents[1][i] + self.f * (parents[2][i] - parents[3][i]));
+            } else {
+                offsprings[0].push(target[i]);
+            }
+        }
+    }
+}
+
+fn center_of_mass(parents: &[&Vec<f64>]) -> Vec<f64> {
+    let mut center = vec![0.0; parents[0].len()];
+    for parent in parents {
+        for i in 0..center.len() {
+            center[i] += parent[i] / parents.len() as f64;
+        }
+    }
+    center
+}
+
+// Tsutsui's center of mass crossover, every parent is paired with its virtual mate mirrored
+// through the center of mass of the parents and the offspring is sampled between them
+pub struct CenterOfMassCrossover {
+    pub parents_count: usize
+}
+
+impl MultiCrossoverFun<f64> for CenterOfMassCrossover {
+    fn parents_count(&self) -> usize {
+        self.parents_count
+    }
+    fn offsprings_count(&self) -> usize {
+        self.parents_count
+    }
+    fn multi_crossover_fun(&self, parents: &[&Vec<f64>], offsprings: &mut [Vec<f64>]) {
+        let center = center_of_mass(parents);
+        for o in 0..parents.len() {
+            for i in 0..center.len() {
+                let mate = 2.0 * center[i] - parents[o][i];
+                offsprings[o].push(parents[o][i] + rand::random::<f64>() * (mate - parents[o][i]));
+            }
+        }
+    }
+}
+
+// Ono's unimodal normal distribution crossover, the first two parents define the main axis
+// and the distance of the third one from it scales the orthogonal spread
+pub struct UnimodalNormalCrossover {
+    pub sigma_xi: f64,
+    pub sigma_eta: f64
+}
+
+impl UnimodalNormalCrossover {
+    // recommended setting, sigma_eta is scaled by the dimension
+    pub fn new(dim: usize) -> Self {
+        UnimodalNormalCrossover { sigma_xi: 0.5, sigma_eta: 0.35 / (dim as f64).sqrt() }
+    }
+}
+
+impl MultiCrossoverFun<f64> for UnimodalNormalCrossover {
+    fn parents_count(&self) -> usize {
+        3
+    }
+    fn offsprings_count(&self) -> usize {
+        2
+    }
+    fn multi_crossover_fun(&self, parents: &[&Vec<f64>], offsprings: &mut [Vec<f64>]) {
+        let dim = parents[0].len();
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let mut rng = rand::thread_rng();
+        let middle: Vec<f64> = (0..dim).map(|i| 0.5 * (parents[0][i] + parents[1][i])).collect();
+        let axis: Vec<f64> = (0..dim).map(|i| parents[1][i] - parents[0][i]).collect();
+        let axis_sq_norm: f64 = axis.iter().map(|a| a * a).sum();
+        // distance of the third parent from the line through the first two
+        let to_third: Vec<f64> = (0..dim).map(|i| parents[2][i] - parents[0][i]).collect();
+        let mut projection = 0.0;
+        if axis_sq_norm > 0.0 {
+            projection = (0..dim).map(|i| to_third[i] * axis[i]).sum::<f64>() / axis_sq_norm;
+        }
+        let distance = (0..dim).map(|i| (to_third[i] - projection * axis[i]).powi(2)).sum::<f64>().sqrt();
+        // normal vector in the subspace orthogonal to the main axis
+        let mut orthogonal: Vec<f64> = (0..dim).map(|_| self.sigma_eta * distance * normal.sample(&mut rng)).collect();
+        if axis_sq_norm > 0.0 {
+            let axis_part = (0..dim).map(|i| orthogonal[i] * axis[i]).sum::<f64>() / axis_sq_norm;
+            for i in 0..dim {
+                orthogonal[i] -= axis_part * axis[i];
+            }
+        }
+        let xi = self.sigma_xi * normal.sample(&mut rng);
+        for i in 0..dim {
+            let step = xi * axis[i] + orthogonal[i];
+            offsprings[0].push(middle[i] + step);
+            offsprings[1].push(middle[i] - step);
+        }
+    }
+}
+
+// Tsutsui's simplex crossover, samples uniformly from the simplex of the parents expanded by epsilon around its center
+pub struct SimplexCrossover {
+    pub parents_count: usize,
+    pub offsprings_count: usize,
+    pub epsilon: f64
+}
+
+impl SimplexCrossover {
+    // recommended expansion rate sqrt(n + 1) for n parents
+    pub fn new(parents_count: usize, offsprings_count: usize) -> Self {
+        SimplexCrossover { parents_count, offsprings_count, epsilon: ((parents_count + 1) as f64).sqrt() }
+    }
+}
+
+impl MultiCrossoverFun<f64> for SimplexCrossover {
+    fn parents_count(&self) -> usize {
+        self.parents_count
+    }
+    fn offsprings_count(&self) -> usize {
+        self.offsprings_count
+    }
+    fn multi_crossover_fun(&self, parents: &[&Vec<f64>], offsprings: &mut [Vec<f64>]) {
+        let dim = parents[0].len();
+        let center = center_of_mass(parents);
+        let expanded: Vec<Vec<f64>> = parents.iter()
+            .map(|parent| (0..dim).map(|i| center[i] + self.epsilon * (parent[i] - center[i])).collect())
+            .collect();
+        for offspring in offsprings.iter_mut() {
+            let mut c = vec![0.0; dim];
+            for k in 1..expanded.len() {
+                let r = rand::random::<f64>().powf(1.0 / k as f64);
+                for i in 0..dim {
+                    c[i] = r * (expanded[k - 1][i] - expanded[k][i] + c[i]);
+                }
+            }
+            let last = &expanded[expanded.len() - 1];
+            offspring.extend((0..dim).map(|i| last[i] + c[i]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn crossover_pair<CrossoverFunT: CrossoverFun<f64>>(crossover_fun: &CrossoverFunT, parents: [&Vec<f64>; 2]) -> [Vec<f64>; 2] {
+        let mut offspring1 = Vec::<f64>::new();
+        let mut offspring2 = Vec::<f64>::new();
+        crossover_fun.crossover_fun(parents, [&mut offspring1, &mut offspring2]);
+        [offspring1, offspring2]
+    }
+
+    #[test]
+    fn test_k_point_complementary() {
+        let parent1 = vec![0.0; 20];
+        let parent2 = vec![1.0; 20];
+        for k in 0..25 {
+            let [offspring1, offspring2] = crossover_pair(&KPointCrossover { k }, [&parent1, &parent2]);
+            let mut switches = 0;
+            for i in 0..parent1.len() {
+                assert_eq!(offspring1[i] + offspring2[i], 1.0);
+                if i > 0 && offspring1[i] != offspring1[i - 1] {
+                    switches += 1;
+                }
+            }
+            assert_eq!(switches, k.min(parent1.len() - 1));
+        }
+    }
+
+    #[test]
+    fn test_sbx_respects_bounds() {
+        let bounds = vec![Bounds { lower: 0.0, upper: 1.0 }; 10];
+        let sbx = SimulatedBinaryCrossover::new(2.0, &bounds);
+        let parent1 = vec![0.01; 10];
+        let parent2 = vec![0.99; 10];
+        for _ in 0..100 {
+            for offspring in crossover_pair(&sbx, [&parent1, &parent2]) {
+                assert!(offspring.iter().all(|x| *x >= 0.0 && *x <= 1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_crossover_arity() {
+        let parent_values: Vec<Vec<f64>> = (0..7).map(|p| vec![p as f64; 5]).collect();
+        let parents: Vec<&Vec<f64>> = parent_values.iter().collect();
+        let pair = PairCrossover { crossover_fun: KPointCrossover { k: 1 } };
+        assert_eq!(multi_crossover_vec(&parents, &pair, 1.0).len(), parents.len());
+        let undx = UnimodalNormalCrossover::new(5);
+        assert_eq!(multi_crossover_vec(&parents, &undx, 1.0).len(), parents.len());
+        // without crossover the parents are copied
+        let de = DifferentialCrossover { f: 0.5, cr: 0.9 };
+        assert_eq!(multi_crossover_vec(&parents, &de, 0.0), parent_values);
+    }
+
+    #[test]
+    fn test_diagonal_keeps_columns() {
+        let parent_values: Vec<Vec<f64>> = (0..4).map(|p| vec![p as f64; 10]).collect();
+        let parents: Vec<&Vec<f64>> = parent_values.iter().collect();
+        let diagonal = DiagonalCrossover { parents_count: 4 };
+        let mut offsprings = vec![Vec::<f64>::new(); 4];
+        diagonal.multi_crossover_fun(&parents, &mut offsprings);
+        for i in 0..10 {
+            let sum: f64 = offsprings.iter().map(|o| o[i]).sum();
+            assert_eq!(sum, 6.0);
+        }
+    }
+
+    #[test]
+    fn test_multi_crossover_values() {
+        let parent_values = vec![vec![0.0, 1.0, 2.0], vec![4.0, 2.0, 0.0], vec![1.0, 1.0, 5.0], vec![3.0, -1.0, 2.0]];
+        let parents: Vec<&Vec<f64>> = parent_values.iter().collect();
+
+        // the whole mutant vector is taken with cr = 1
+        let mut offsprings = vec![Vec::<f64>::new(); 1];
+        DifferentialCrossover { f: 0.5, cr: 1.0 }.multi_crossover_fun(&parents, &mut offsprings);
+        assert_eq!(offsprings[0], vec![4.0 + 0.5 * (1.0 - 3.0), 2.0 + 0.5 * (1.0 + 1.0), 0.0 + 0.5 * (5.0 - 2.0)]);
+
+        // every offspring lies between its parent and the parent mirrored through the center of mass
+        let center = center_of_mass(&parents);
+        assert_eq!(center, vec![2.0, 0.75, 2.25]);
+        let mut offsprings = vec![Vec::<f64>::new(); 4];
+        CenterOfMassCrossover { parents_count: 4 }.multi_crossover_fun(&parents, &mut offsprings);
+        for o in 0..4 {
+            for i in 0..3 {
+                let mate = 2.0 * center[i] - parents[o][i];
+                assert!(offsprings[o][i] >= parents[o][i].min(mate) && offsprings[o][i] <= parents[o][i].max(mate));
+            }
+        }
+
+        // the offsprings are symmetric around the middle of the first two parents
+        let mut offsprings = vec![Vec::<f64>::new(); 2];
+        UnimodalNormalCrossover::new(3).multi_crossover_fun(&parents[..3], &mut offsprings);
+        for i in 0..3 {
+            assert!((offsprings[0][i] + offsprings[1][i] - parents[0][i] - parents[1][i]).abs() < 1.0e-9);
+        }
+
+        // in one dimension the expanded simplex of 0 and 1 is the interval 0.5 -+ sqrt(3) / 2
+        let (low, high) = (vec![0.0], vec![1.0]);
+        let spx = SimplexCrossover::new(2, 10);
+        let mut offsprings = vec![Vec::<f64>::new(); 10];
+        spx.multi_crossover_fun(&[&low, &high], &mut offsprings);
+        let half_width = 0.5 * 3.0f64.sqrt();
+        assert!(offsprings.iter().all(|o| o[0] >= 0.5 - half_width - 1.0e-9 && o[0] <= 0.5 + half_width + 1.0e-9));
+    }
+}
@@ -0,0 +1,431 @@
+use std::rc::Rc;
+
+use crate::opt_traits::*;
+use crate::opt_data::*;
+
+fn project_to_bounds(values: &mut Vec<f64>, bounds: &Vec<Bounds>) {
+    for d in 0..bounds.len().min(values.len()) {
+        values[d] = values[d].clamp(bounds[d].lower, bounds[d].upper);
+    }
+}
+
+fn initial_steps(dim: usize, initial_step: f64, bounds: &Vec<Bounds>) -> Vec<f64> {
+    (0..dim)
+        .map(|d| if d < bounds.len() { initial_step * (bounds[d].upper - bounds[d].lower) } else { initial_step })
+        .collect()
+}
+
+#[derive(Clone)]
+pub struct NelderMeadParams {
+    // size of the initial simplex relative to the bounds size, absolute if there are no bounds
+    pub initial_step: f64,
+    // dimension dependent coefficients of Gao and Han, better for higher dimensions
+    pub adaptive: bool,
+    // simplex is considered collapsed when the fitness spread drops below the tolerance
+    pub tolerance: f64,
+    pub bounds: Vec<Bounds>
+}
+
+impl NelderMeadParams {
+    pub fn new(bounds: &Vec<Bounds>) -> Self {
+        NelderMeadParams { initial_step: 0.05, adaptive: true, tolerance: 1.0e-12, bounds: bounds.clone() }
+    }
+
+    // reflection, expansion, contraction and shrink coefficients
+    fn coefficients(&self, dim: usize) -> (f64, f64, f64, f64) {
+        if self.adaptive {
+            let n = dim as f64;
+            (1.0, 1.0 + 2.0 / n, 0.75 - 0.5 / n, 1.0 - 1.0 / n)
+        } else {
+            (1.0, 2.0, 0.5, 0.5)
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct NelderMeadSimplex {
+    pub points: Vec<FloatVec>,
+    pub fitness: Vec<f64>
+}
+
+impl NelderMeadSimplex {
+    pub fn new<FitnessFuncT: FitnessFunc<FloatVec> + ?Sized>(fitness_func: &FitnessFuncT, start: &FloatVec, params: &NelderMeadParams) -> Self {
+        let dim = start.dim();
+        let steps = initial_steps(dim, params.initial_step, &params.bounds);
+        let mut points = Vec::<FloatVec>::with_capacity(dim + 1);
+        points.push(start.clone());
+        for d in 0..dim {
+            let mut point = start.clone();
+            point.values[d] += steps[d];
+            // step to the other side if the vertex would end up on the bound
+            if d < params.bounds.len() && point.values[d] > params.bounds[d].upper {
+                point.values[d] = start.values[d] - steps[d];
+            }
+            project_to_bounds(&mut point.values, &params.bounds);
+            points.push(point);
+        }
+        let fitness = points.iter().map(|p| fitness_func.eval(p)).collect();
+        let mut simplex = NelderMeadSimplex { points, fitness };
+        simplex.sort();
+        simplex
+    }
+
+    fn sort(&mut self) {
+        let mut order: Vec<usize> = (0..self.points.len()).collect();
+        order.sort_by(|a, b| self.fitness[*a].total_cmp(&self.fitness[*b]));
+        self.points = order.iter().map(|i| self.points[*i].clone()).collect();
+        self.fitness = order.iter().map(|i| self.fitness[*i]).collect();
+    }
+
+    pub fn best(&self) -> (&FloatVec, f64) {
+        (&self.points[0], self.fitness[0])
+    }
+
+    pub fn is_collapsed(&self, tolerance: f64) -> bool {
+        self.fitness[self.fitness.len() - 1] - self.fitness[0] <= tolerance
+    }
+
+    fn affine_point(centroid: &Vec<f64>, worst: &Vec<f64>, coef: f64, bounds: &Vec<Bounds>) -> FloatVec {
+        let mut values: Vec<f64> = (0..centroid.len()).map(|d| centroid[d] + coef * (centroid[d] - worst[d])).collect();
+        project_to_bounds(&mut values, bounds);
+        FloatVec { values }
+    }
+
+    // performs one iteration of the method, the simplex stays sorted from the best to the worst vertex
+    pub fn step<FitnessFuncT: FitnessFunc<FloatVec> + ?Sized>(&mut self, fitness_func: &FitnessFuncT, params: &NelderMeadParams) {
+        let n = self.points.len() - 1;
+        let (reflection, expansion, contraction, shrink) = params.coefficients(n);
+        let mut centroid = vec![0.0; n];
+        for p in 0..n {
+            for d in 0..n {
+                centroid[d] += self.points[p].values[d] / (n as f64);
+            }
+        }
+        let worst = self.points[n].values.clone();
+        let reflected = Self::affine_point(&centroid, &worst, reflection, &params.bounds);
+        let reflected_fitness = fitness_func.eval(&reflected);
+
+        if reflected_fitness < self.fitness[0] {
+            let expanded = Self::affine_point(&centroid, &worst, reflection * expansion, &params.bounds);
+            let expanded_fitness = fitness_func.eval(&expanded);
+            if expanded_fitness < reflected_fitness {
+                self.points[n] = expanded;
+                self.fitness[n] = expanded_fitness;
+            } else {
+                self.points[n] = reflected;
+                self.fitness[n] = reflected_fitness;
+            }
+        } else if reflected_fitness < self.fitness[n - 1] {
+            self.points[n] = reflected;
+            self.fitness[n] = reflected_fitness;
+        } else {
+            let outside = reflected_fitness < self.fitness[n];
+            let contracted = if outside {
+                Self::affine_point(&centroid, &worst, reflection * contraction, &params.bounds)
+            } else {
+                Self::affine_point(&centroid, &worst, -contraction, &params.bounds)
+            };
+            let contracted_fitness = fitness_func.eval(&contracted);
+            let threshold = if outside { reflected_fitness } else { self.fitness[n] };
+            if contracted_fitness <= threshold {
+                self.points[n] = contracted;
+                self.fitness[n] = contracted_fitness;
+            } else {
+                for p in 1..=n {
+                    for d in 0..n {
+                        let best_value = self.points[0].values[d];
+                        self.points[p].values[d] = best_value + shrink * (self.points[p].values[d] - best_value);
+                    }
+                    self.fitness[p] = fitness_func.eval(&self.points[p]);
+                }
+            }
+        }
+        self.sort();
+    }
+}
+
+pub fn nelder_mead_search<
+        FitnessFuncT : FitnessFunc<FloatVec>,
+        InitFuncT: InitFunc<FloatVec>,
+        TerminationCondT: TerminationCond<FloatVec>
+    >(
+        fitness_func: &mut FitnessFuncT,
+        init_func: InitFuncT,
+        params: &NelderMeadParams,
+        termination_cond: &TerminationCondT
+    )
+    -> (BSFSingleObjSolution<FloatVec>, BSFSingleObjStatistics)
+{
+    let mut simplex = NelderMeadSimplex::new(fitness_func, &init_func.init(), params);
+    let mut stats = BSFSingleObjStatistics { fitness: Vec::<f64>::new() };
+    let mut iter: usize = 0;
+    let mut diff = f64::INFINITY;
+    stats.fitness.push(simplex.best().1);
+    while !termination_cond.eval(iter, diff) {
+        let prev_fitness = simplex.best().1;
+        // collapsed simplex would only waste evaluations, keep reporting the result instead
+        if !simplex.is_collapsed(params.tolerance) {
+            simplex.step(fitness_func, params);
+        }
+        diff = simplex.best().1 - prev_fitness;
+        stats.fitness.push(simplex.best().1);
+        iter += 1;
+    }
+    let (value, fitness) = simplex.best();
+    (BSFSingleObjSolution::<FloatVec> { value: value.clone(), fitness }, stats)
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum PatternSearchMethod {
+    // polls all 2n coordinate directions and moves to the best improving one
+    Compass,
+    // exploratory moves along each coordinate followed by a pattern move
+    HookeJeeves
+}
+
+#[derive(Clone)]
+pub struct PatternSearchParams {
+    pub method: PatternSearchMethod,
+    // initial step relative to the bounds size, absolute if there are no bounds
+    pub initial_step: f64,
+    pub step_reduction: f64,
+    // search stops moving once all steps are below this value
+    pub min_step: f64,
+    pub bounds: Vec<Bounds>
+}
+
+impl PatternSearchParams {
+    pub fn new(method: PatternSearchMethod, bounds: &Vec<Bounds>) -> Self {
+        PatternSearchParams { method, initial_step: 0.1, step_reduction: 0.5, min_step: 1.0e-9, bounds: bounds.clone() }
+    }
+}
+
+#[derive(Clone)]
+pub struct PatternSearchState {
+    pub value: FloatVec,
+    pub fitness: f64,
+    pub steps: Vec<f64>
+}
+
+impl PatternSearchState {
+    pub fn new<FitnessFuncT: FitnessFunc<FloatVec> + ?Sized>(fitness_func: &FitnessFuncT, start: &FloatVec, params: &PatternSearchParams) -> Self {
+        let mut value = start.clone();
+        project_to_bounds(&mut value.values, &params.bounds);
+        let fitness = fitness_func.eval(&value);
+        PatternSearchState { steps: initial_steps(value.dim(), params.initial_step, &params.bounds), value, fitness }
+    }
+
+    pub fn is_converged(&self, min_step: f64) -> bool {
+        self.steps.iter().all(|s| *s < min_step)
+    }
+
+    fn reduce_steps(&mut self, step_reduction: f64) {
+        for step in &mut self.steps {
+            *step *= step_reduction;
+        }
+    }
+
+    // tries +-step along every coordinate and keeps each improving move
+    fn explore<FitnessFuncT: FitnessFunc<FloatVec> + ?Sized>(&self, fitness_func: &FitnessFuncT, base: &FloatVec, base_fitness: f64, bounds: &Vec<Bounds>) -> (FloatVec, f64) {
+        let mut current = base.clone();
+        let mut current_fitness = base_fitness;
+        let mut trial = base.clone();
+        for d in 0..current.dim() {
+            for sign in [1.0, -1.0] {
+                trial.values.clone_from(&current.values);
+                trial.values[d] += sign * self.steps[d];
+                project_to_bounds(&mut trial.values, bounds);
+                let trial_fitness = fitness_func.eval(&trial);
+                if trial_fitness < current_fitness {
+                    current.values.clone_from(&trial.values);
+                    current_fitness = trial_fitness;
+                    break;
+                }
+            }
+        }
+        (current, current_fitness)
+    }
+
+    pub fn step<FitnessFuncT: FitnessFunc<FloatVec> + ?Sized>(&mut self, fitness_func: &FitnessFuncT, params: &PatternSearchParams) {
+        match params.method {
+            PatternSearchMethod::Compass => {
+                let mut best = self.value.clone();
+                let mut best_fitness = self.fitness;
+                let mut trial = self.value.clone();
+                for d in 0..self.value.dim() {
+                    for sign in [1.0, -1.0] {
+                        trial.values.clone_from(&self.value.values);
+                        trial.values[d] += sign * self.steps[d];
+                        project_to_bounds(&mut trial.values, &params.bounds);
+                        let trial_fitness = fitness_func.eval(&trial);
+                        if trial_fitness < best_fitness {
+                            best.values.clone_from(&trial.values);
+                            best_fitness = trial_fitness;
+                        }
+                    }
+                }
+                if best_fitness < self.fitness {
+                    self.value = best;
+                    self.fitness = best_fitness;
+                } else {
+                    self.reduce_steps(params.step_reduction);
+                }
+            },
+            PatternSearchMethod::HookeJeeves => {
+                let (mut explored, mut explored_fitness) = self.explore(fitness_func, &self.value, self.fitness, &params.bounds);
+                if explored_fitness >= self.fitness {
+                    self.reduce_steps(params.step_reduction);
+                    return;
+                }
+                // keep following the pattern direction while it brings improvement
+                loop {
+                    let mut pattern = explored.clone();
+                    for d in 0..pattern.dim() {
+                        pattern.values[d] += explored.values[d] - self.value.values[d];
+                    }
+                    project_to_bounds(&mut pattern.values, &params.bounds);
+                    let pattern_fitness = fitness_func.eval(&pattern);
+                    let (next, next_fitness) = self.explore(fitness_func, &pattern, pattern_fitness, &params.bounds);
+                    self.value = explored;
+                    self.fitness = explored_fitness;
+                    if next_fitness >= self.fitness {
+                        break;
+                    }
+                    explored = next;
+                    explored_fitness = next_fitness;
+                }
+            }
+        }
+    }
+}
+
+pub fn pattern_search<
+        FitnessFuncT : FitnessFunc<FloatVec>,
+        InitFuncT: InitFunc<FloatVec>,
+        TerminationCondT: TerminationCond<FloatVec>
+    >(
+        fitness_func: &mut FitnessFuncT,
+        init_func: InitFuncT,
+        params: &PatternSearchParams,
+        termination_cond: &TerminationCondT
+    )
+    -> (BSFSingleObjSolution<FloatVec>, BSFSingleObjStatistics)
+{
+    let mut state = PatternSearchState::new(fitness_func, &init_func.init(), params);
+    let mut stats = BSFSingleObjStatistics { fitness: Vec::<f64>::new() };
+    let mut iter: usize = 0;
+    let mut diff = f64::INFINITY;
+    stats.fitness.push(state.fitness);
+    while !termination_cond.eval(iter, diff) {
+        let prev_fitness = state.fitness;
+        if !state.is_converged(params.min_step) {
+            state.step(fitness_func, params);
+        }
+        diff = state.fitness - prev_fitness;
+        stats.fitness.push(state.fitness);
+        iter += 1;
+    }
+    (BSFSingleObjSolution::<FloatVec> { value: state.value, fitness: state.fitness }, stats)
+}
+
+// Improvement step for memetic algorithms, runs a short Nelder-Mead from the mutated individual
+#[derive(Clone)]
+pub struct NelderMeadLocalOpt {
+    pub fitness_func: Rc<dyn FitnessFunc<FloatVec>>,
+    pub params: NelderMeadParams,
+    pub max_iters: usize
+}
+
+impl PerturbeMutOp<FloatVec> for NelderMeadLocalOpt {
+    fn eval(&self, data: &mut FloatVec) {
+        let mut simplex = NelderMeadSimplex::new(self.fitness_func.as_ref(), data, &self.params);
+        for _ in 0..self.max_iters {
+            if simplex.is_collapsed(self.params.tolerance) {
+                break;
+            }
+            simplex.step(self.fitness_func.as_ref(), &self.params);
+        }
+        data.values.clone_from(&simplex.best().0.values);
+    }
+}
+
+// Improvement step for memetic algorithms, runs a short pattern search from the mutated individual
+#[derive(Clone)]
+pub struct PatternSearchLocalOpt {
+    pub fitness_func: Rc<dyn FitnessFunc<FloatVec>>,
+    pub params: PatternSearchParams,
+    pub max_iters: usize
+}
+
+impl PerturbeMutOp<FloatVec> for PatternSearchLocalOpt {
+    fn eval(&self, data: &mut FloatVec) {
+        let mut state = PatternSearchState::new(self.fitness_func.as_ref(), data, &self.params);
+        for _ in 0..self.max_iters {
+            if state.is_converged(self.params.min_step) {
+                break;
+            }
+            state.step(self.fitness_func.as_ref(), &self.params);
+        }
+        data.values.clone_from(&state.value.values);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::funcs::*;
+
+    const EPSILON: f64 = 1.0e-6;
+
+    fn check_nelder_mead(adaptive: bool, dim: usize) {
+        let fitness = SphereFunc { o: vec![1.0; dim] };
+        let mut params = NelderMeadParams::new(&vec![Bounds { lower: -10.0, upper: 10.0 }; dim]);
+        params.adaptive = adaptive;
+        let mut simplex = NelderMeadSimplex::new(&fitness, &FloatVec { values: vec![-3.0; dim] }, &params);
+        for _ in 0..5000 {
+            simplex.step(&fitness, &params);
+        }
+        assert!(simplex.best().1 < EPSILON);
+    }
+
+    fn check_pattern_search(method: PatternSearchMethod, dim: usize) {
+        let fitness = SphereFunc { o: vec![1.0; dim] };
+        let params = PatternSearchParams::new(method, &vec![Bounds { lower: -10.0, upper: 10.0 }; dim]);
+        let mut state = PatternSearchState::new(&fitness, &FloatVec { values: vec![-3.0; dim] }, &params);
+        while !state.is_converged(params.min_step) {
+            state.step(&fitness, &params);
+        }
+        assert!(state.fitness < EPSILON);
+    }
+
+    #[test]
+    fn test_nelder_mead_sphere() {
+        check_nelder_mead(false, 2);
+        check_nelder_mead(true, 2);
+        check_nelder_mead(true, 10);
+    }
+
+    #[test]
+    fn test_pattern_search_sphere() {
+        check_pattern_search(PatternSearchMethod::Compass, 2);
+        check_pattern_search(PatternSearchMethod::Compass, 10);
+        check_pattern_search(PatternSearchMethod::HookeJeeves, 2);
+        check_pattern_search(PatternSearchMethod::HookeJeeves, 10);
+    }
+
+    #[test]
+    fn test_bounds_respected() {
+        // unconstrained optimum lies outside of the bounds
+        let fitness = SphereFunc { o: vec![5.0; 3] };
+        let bounds = vec![Bounds { lower: -1.0, upper: 1.0 }; 3];
+        let params = PatternSearchParams::new(PatternSearchMethod::HookeJeeves, &bounds);
+        let mut state = PatternSearchState::new(&fitness, &FloatVec { values: vec![0.0; 3] }, &params);
+        while !state.is_converged(params.min_step) {
+            state.step(&fitness, &params);
+        }
+        for value in &state.value.values {
+            assert!((value - 1.0).abs() < EPSILON);
+        }
+    }
+}
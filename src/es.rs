@@ -0,0 +1,306 @@
+use rand::Rng;
+use rand_distr::{Normal, Distribution};
+
+use crate::opt_traits::*;
+use crate::opt_data::*;
+use crate::bound_handling::*;
+
+// Evolution strategy genotype, object variables with their own strategy parameters.
+// One sigma: sigmas.len() == 1, n sigmas: sigmas.len() == n,
+// correlated mutations additionally hold n(n-1)/2 rotation angles.
+#[derive(Clone)]
+pub struct EsFloatVec {
+    pub object: FloatVec,
+    pub sigmas: Vec<f64>,
+    pub rotations: Vec<f64>
+}
+
+impl OptData for EsFloatVec {
+    fn dim(&self) -> usize {
+        self.object.dim()
+    }
+}
+
+// Evaluates only the object variables with a real function
+#[derive(Clone)]
+pub struct EsFunc<RealFunc: FitnessFunc<FloatVec>> {
+    pub real_func: RealFunc
+}
+
+impl<RealFunc: FitnessFunc<FloatVec>> FitnessFunc<EsFloatVec> for EsFunc<RealFunc> {
+    fn eval(&self, data: &EsFloatVec) -> f64 {
+        self.real_func.eval(&data.object)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EsStrategyType {
+    OneSigma,
+    NSigmas,
+    Correlated
+}
+
+pub fn es_rotations_count(dim: usize) -> usize {
+    dim * dim.saturating_sub(1) / 2
+}
+
+#[derive(Clone)]
+pub struct InitRandomEsPopulation {
+    pub size: usize,
+    pub vec_size: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub bounds: Vec<Bounds>,
+    pub bound_handling: BoundHandling,
+    pub strategy: EsStrategyType,
+    pub init_sigma: f64
+}
+
+impl InitRandomEsPopulation {
+    fn rand_data(&self) -> EsFloatVec {
+        let normal = Normal::new(self.mean, self.std_dev).unwrap();
+        let mut values = Vec::<f64>::with_capacity(self.vec_size);
+        for i in 0..self.vec_size {
+            let mut value = normal.sample(&mut rand::thread_rng());
+            if self.bounds.len() == self.vec_size {
+                value = handle_bound(value, self.mean, &self.bounds[i], self.bound_handling);
+            }
+            values.push(value);
+        }
+        let sigmas_count = if self.strategy == EsStrategyType::OneSigma { 1 } else { self.vec_size };
+        let rotations_count = if self.strategy == EsStrategyType::Correlated { es_rotations_count(self.vec_size) } else { 0 };
+        EsFloatVec {
+            object: FloatVec { values },
+            sigmas: vec![self.init_sigma; sigmas_count],
+            rotations: vec![0.0; rotations_count]
+        }
+    }
+}
+
+impl InitFunc<EsFloatVec> for InitRandomEsPopulation {
+    fn init(&self) -> EsFloatVec {
+        self.rand_data()
+    }
+}
+
+impl InitPopulation<EsFloatVec> for InitRandomEsPopulation {
+    fn init(&self) -> Vec<EsFloatVec> {
+        let mut population = Vec::<EsFloatVec>::with_capacity(self.size);
+        for _ in 0..self.size {
+            population.push(self.rand_data());
+        }
+        population
+    }
+}
+
+// rotates the vector by all angles, pairs of axes are visited in the same order as the angles are stored
+fn es_rotate(values: &mut Vec<f64>, rotations: &Vec<f64>) {
+    let dim = values.len();
+    let mut k = 0;
+    for i in 0..dim {
+        for j in (i + 1)..dim {
+            let (sin, cos) = rotations[k].sin_cos();
+            let vi = values[i];
+            let vj = values[j];
+            values[i] = vi * cos - vj * sin;
+            values[j] = vi * sin + vj * cos;
+            k += 1;
+        }
+    }
+}
+
+// Log-normal self-adaptation of the strategy parameters stored in every individual
+#[derive(Clone)]
+pub struct SelfAdaptiveEsMutOp {
+    // lower bound for every sigma, prevents premature convergence of the step sizes
+    pub min_sigma: f64,
+    // angle mutation strength, 5 degrees recommended by Schwefel
+    pub beta: f64,
+    pub bounds: Vec<Bounds>,
+    pub bound_handling: BoundHandling
+}
+
+impl SelfAdaptiveEsMutOp {
+    pub fn new(bounds: &Vec<Bounds>) -> Self {
+        SelfAdaptiveEsMutOp { min_sigma: 1.0e-10, beta: 5.0f64.to_radians(), bounds: bounds.clone(), bound_handling: BoundHandling::Clamp }
+    }
+}
+
+impl PerturbeMutOp<EsFloatVec> for SelfAdaptiveEsMutOp {
+    fn eval(&self, data: &mut EsFloatVec) {
+        let mut rng = rand::thread_rng();
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let n = data.dim() as f64;
+        if data.sigmas.len() == 1 {
+            let tau = 1.0 / n.sqrt();
+            data.sigmas[0] = (data.sigmas[0] * (tau * normal.sample(&mut rng)).exp()).max(self.min_sigma);
+        } else {
+            let tau_global = 1.0 / (2.0 * n).sqrt();
+            let tau_local = 1.0 / (2.0 * n.sqrt()).sqrt();
+            let global = tau_global * normal.sample(&mut rng);
+            for sigma in &mut data.sigmas {
+                *sigma = (*sigma * (global + tau_local * normal.sample(&mut rng)).exp()).max(self.min_sigma);
+            }
+        }
+        for angle in &mut data.rotations {
+            *angle += self.beta * normal.sample(&mut rng);
+            // keep angles in [-pi, pi]
+            if angle.abs() > std::f64::consts::PI {
+                *angle -= 2.0 * std::f64::consts::PI * angle.signum();
+            }
+        }
+        let mut steps: Vec<f64> = (0..data.dim())
+            .map(|i| data.sigmas[i.min(data.sigmas.len() - 1)] * normal.sample(&mut rng))
+            .collect();
+        if !data.rotations.is_empty() {
+            es_rotate(&mut steps, &data.rotations);
+        }
+        for i in 0..data.dim() {
+            let parent = data.object.values[i];
+            let mut value = parent + steps[i];
+            if i < self.bounds.len() {
+                value = handle_bound(value, parent, &self.bounds[i], self.bound_handling);
+            }
+            data.object.values[i] = value;
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum EsRecombinationType {
+    // offspring copies the value from the first parent
+    None,
+    // every value is taken from a randomly chosen parent
+    Discrete,
+    // every value is the mean of the parents
+    Intermediate
+}
+
+fn es_recombine(parents: [&Vec<f64>; 2], offspring: &mut Vec<f64>, recombination: EsRecombinationType) {
+    offspring.clear();
+    for i in 0..parents[0].len() {
+        let value = match recombination {
+            EsRecombinationType::None => parents[0][i],
+            EsRecombinationType::Discrete => parents[rand::thread_rng().gen_range(0..2)][i],
+            EsRecombinationType::Intermediate => 0.5 * (parents[0][i] + parents[1][i])
+        };
+        offspring.push(value);
+    }
+}
+
+// Recombines object variables and strategy parameters independently,
+// usual setting is discrete for the object variables and intermediate for the strategy parameters
+pub struct EsCrossover {
+    pub object: EsRecombinationType,
+    pub strategy: EsRecombinationType
+}
+
+impl Crossover<EsFloatVec> for EsCrossover {
+    fn crossover(&self, population: &Vec<EsFloatVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<EsFloatVec>) {
+        offsprings.clear();
+        for i in (0..parents_indices.len()).step_by(2) {
+            if i + 1 >= parents_indices.len() {
+                continue;
+            }
+            for o in 0..2 {
+                let parent1 = &population[parents_indices[i + o]];
+                let parent2 = &population[parents_indices[i + 1 - o]];
+                let mut offspring = parent1.clone();
+                es_recombine([&parent1.object.values, &parent2.object.values], &mut offspring.object.values, self.object);
+                es_recombine([&parent1.sigmas, &parent2.sigmas], &mut offspring.sigmas, self.strategy);
+                es_recombine([&parent1.rotations, &parent2.rotations], &mut offspring.rotations, self.strategy);
+                offsprings.push(offspring);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::funcs::*;
+    use crate::opt_search::*;
+    use crate::selection::*;
+    use crate::replacement::*;
+    use crate::termination::*;
+
+    const EPSILON: f64 = 1.0e-9;
+
+    #[test]
+    fn test_rotation_keeps_length() {
+        let mut values = vec![1.0, -2.0, 3.0, 0.5];
+        let length: f64 = values.iter().map(|v| v * v).sum::<f64>().sqrt();
+        let rotations = vec![0.1, -0.7, 1.3, 2.0, -3.0, 0.4];
+        es_rotate(&mut values, &rotations);
+        let rotated_length: f64 = values.iter().map(|v| v * v).sum::<f64>().sqrt();
+        assert!((length - rotated_length).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_rotation_2d() {
+        let mut values = vec![1.0, 0.0];
+        es_rotate(&mut values, &vec![std::f64::consts::FRAC_PI_2]);
+        assert!(values[0].abs() < EPSILON);
+        assert!((values[1] - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_self_adaptation() {
+        let bounds = vec![Bounds { lower: -1.0, upper: 1.0 }; 5];
+        let mut_op = SelfAdaptiveEsMutOp { min_sigma: 0.5, ..SelfAdaptiveEsMutOp::new(&bounds) };
+        let parent = EsFloatVec { object: FloatVec { values: vec![0.0; 5] }, sigmas: vec![1.0; 5], rotations: vec![0.0; es_rotations_count(5)] };
+        let (mut offspring1, mut offspring2) = (parent.clone(), parent.clone());
+        mut_op.eval(&mut offspring1);
+        mut_op.eval(&mut offspring2);
+        // the global and local factors are sampled for every individual and every sigma
+        assert!(offspring1.sigmas != offspring2.sigmas);
+        assert!(offspring1.sigmas.iter().any(|sigma| *sigma != offspring1.sigmas[0]));
+        for _ in 0..100 {
+            mut_op.eval(&mut offspring1);
+            assert!(offspring1.sigmas.iter().all(|sigma| *sigma >= 0.5));
+            assert!(offspring1.object.values.iter().all(|value| value.abs() <= 1.0));
+            assert!(offspring1.rotations.iter().all(|angle| angle.abs() <= std::f64::consts::PI));
+        }
+    }
+
+    #[test]
+    fn test_strategy_recombination() {
+        let population = vec![
+            EsFloatVec { object: FloatVec { values: vec![0.0, 0.0] }, sigmas: vec![1.0, 1.0], rotations: vec![0.2] },
+            EsFloatVec { object: FloatVec { values: vec![2.0, 2.0] }, sigmas: vec![3.0, 3.0], rotations: vec![0.4] }];
+        let mut offsprings = Vec::new();
+        EsCrossover { object: EsRecombinationType::None, strategy: EsRecombinationType::Intermediate }.crossover(&population, &vec![0, 1], &mut offsprings);
+        assert_eq!(offsprings.len(), 2);
+        assert_eq!(offsprings[0].object.values, vec![0.0, 0.0]);
+        assert_eq!(offsprings[1].object.values, vec![2.0, 2.0]);
+        for offspring in &offsprings {
+            assert_eq!(offspring.sigmas, vec![2.0, 2.0]);
+            assert!((offspring.rotations[0] - 0.3).abs() < EPSILON);
+        }
+        EsCrossover { object: EsRecombinationType::Intermediate, strategy: EsRecombinationType::Discrete }.crossover(&population, &vec![0, 1], &mut offsprings);
+        for offspring in &offsprings {
+            assert_eq!(offspring.object.values, vec![1.0, 1.0]);
+            assert!(offspring.sigmas.iter().all(|sigma| *sigma == 1.0 || *sigma == 3.0));
+            assert!(offspring.rotations[0] == 0.2 || offspring.rotations[0] == 0.4);
+        }
+    }
+
+    #[test]
+    fn test_sphere_search() {
+        let dim = 5;
+        let bounds = vec![Bounds { lower: -5.0, upper: 5.0 }; dim];
+        let init_population = InitRandomEsPopulation { size: 30, vec_size: dim, mean: 0.0, std_dev: 2.0, bounds: bounds.clone(),
+            bound_handling: BoundHandling::Clamp, strategy: EsStrategyType::NSigmas, init_sigma: 1.0 };
+        let (solution, stats): (BSFSingleObjSolution<EsFloatVec>, BSFSingleObjStatistics) = evolutionary_search(
+            &mut EsFunc { real_func: SphereFunc { o: vec![1.0; dim] } },
+            init_population,
+            &TournamentSelection { select_count: 30, rounds_count: 2 },
+            &EsCrossover { object: EsRecombinationType::Discrete, strategy: EsRecombinationType::Intermediate },
+            SelfAdaptiveEsMutOp::new(&bounds),
+            &TruncationReplacementStrategy {},
+            &MaxIterTerminationCond { n_iters: 200 },
+            false);
+        assert!(solution.fitness < 1.0e-3 * stats.fitness[0], "{} {}", solution.fitness, stats.fitness[0]);
+    }
+}
@@ -0,0 +1,271 @@
+use std::rc::Rc;
+
+use crate::*;
+
+// generated instances up to this size get the optimum as best_known when Held-Karp fits
+pub const EXACT_MAX_GROUPS: usize = 20;
+
+// Held-Karp stores 2^(groups - 1) * vertices values
+pub const HELD_KARP_MAX_STATES: usize = 1 << 26;
+
+// Exact solvers working on groups of vertices, TSP is solved as GTSP with one vertex per group.
+// The tour starts in the smallest group, returns the tour and its length.
+
+fn held_karp<DistFunT: Fn(usize, usize) -> f64>(groups: &[Vec<usize>], dist: &DistFunT) -> (Vec<GroupVert>, f64) {
+    let start_group = (0..groups.len()).min_by_key(|g| groups[*g].len()).unwrap();
+    if groups.len() == 1 {
+        return (vec![GroupVert { group: start_group, vert: 0 }], 0.0);
+    }
+    // vertices of the other groups with the bit of their group
+    let others: Vec<usize> = (0..groups.len()).filter(|g| *g != start_group).collect();
+    let mut verts = Vec::<(GroupVert, usize)>::new();
+    for (bit, g) in others.iter().enumerate() {
+        for v in 0..groups[*g].len() {
+            verts.push((GroupVert { group: *g, vert: v }, bit));
+        }
+    }
+    let vert = |i: usize| groups[verts[i].0.group][verts[i].0.vert];
+    let vert_count = verts.len();
+    let full = (1usize << others.len()) - 1;
+    if (full + 1) * vert_count > HELD_KARP_MAX_STATES {
+        panic!("too many groups for Held-Karp");
+    }
+    // dp[set * vert_count + i] is the shortest path from the start vertex to the vertex i
+    // visiting exactly the groups in the set
+    let mut dp = vec![f64::INFINITY; (full + 1) * vert_count];
+    let mut best = (Vec::<GroupVert>::new(), f64::INFINITY);
+    for start in 0..groups[start_group].len() {
+        let start_vert = groups[start_group][start];
+        dp.fill(f64::INFINITY);
+        for i in 0..vert_count {
+            dp[(1 << verts[i].1) * vert_count + i] = dist(start_vert, vert(i));
+        }
+        for set in 1..=full {
+            for i in 0..vert_count {
+                let len = dp[set * vert_count + i];
+                if set & (1 << verts[i].1) == 0 || len == f64::INFINITY {
+                    continue;
+                }
+                for j in 0..vert_count {
+                    let bit = 1 << verts[j].1;
+                    if set & bit != 0 {
+                        continue;
+                    }
+                    let next_len = len + dist(vert(i), vert(j));
+                    let next = &mut dp[(set | bit) * vert_count + j];
+                    if next_len < *next {
+                        *next = next_len;
+                    }
+                }
+            }
+        }
+        let (last, len) = (0..vert_count)
+            .map(|i| (i, dp[full * vert_count + i] + dist(vert(i), start_vert)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        if len >= best.1 {
+            continue;
+        }
+        // backtracking by recomputing the transitions, the values are bitwise equal
+        let mut tour = vec![verts[last].0];
+        let (mut set, mut i) = (full, last);
+        while set != 1 << verts[i].1 {
+            let prev_set = set & !(1 << verts[i].1);
+            let j = (0..vert_count)
+                .find(|j| prev_set & (1 << verts[*j].1) != 0 && dp[prev_set * vert_count + j] + dist(vert(*j), vert(i)) == dp[set * vert_count + i])
+                .unwrap();
+            tour.push(verts[j].0);
+            (set, i) = (prev_set, j);
+        }
+        tour.push(GroupVert { group: start_group, vert: start });
+        tour.reverse();
+        best = (tour, len);
+    }
+    best
+}
+
+struct BranchAndBound<'a, DistFunT: Fn(usize, usize) -> f64> {
+    groups: &'a [Vec<usize>],
+    dist: &'a DistFunT,
+    // cheapest edge entering the group from another group
+    min_in: Vec<f64>,
+    visited: Vec<bool>,
+    path: Vec<GroupVert>,
+    best: (Vec<GroupVert>, f64)
+}
+
+impl<'a, DistFunT: Fn(usize, usize) -> f64> BranchAndBound<'a, DistFunT> {
+    fn vert(&self, gv: &GroupVert) -> usize {
+        self.groups[gv.group][gv.vert]
+    }
+
+    // remaining_bound is the sum of min_in of the unvisited groups and the start group
+    fn search(&mut self, len: f64, remaining_bound: f64) {
+        let curr = self.vert(self.path.last().unwrap());
+        if self.path.len() == self.groups.len() {
+            let tour_len = len + (self.dist)(curr, self.vert(&self.path[0]));
+            if tour_len < self.best.1 {
+                self.best = (self.path.clone(), tour_len);
+            }
+            return;
+        }
+        let mut children = Vec::<(f64, GroupVert)>::new();
+        for g in 0..self.groups.len() {
+            if self.visited[g] {
+                continue;
+            }
+            for v in 0..self.groups[g].len() {
+                children.push(((self.dist)(curr, self.groups[g][v]), GroupVert { group: g, vert: v }));
+            }
+        }
+        children.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for (cost, child) in children {
+            let child_bound = remaining_bound - self.min_in[child.group];
+            if len + cost + child_bound >= self.best.1 {
+                continue;
+            }
+            self.visited[child.group] = true;
+            self.path.push(child);
+            self.search(len + cost, child_bound);
+            self.path.pop();
+            self.visited[child.group] = false;
+        }
+    }
+}
+
+// depth first search from the initial solution, the nodes are pruned by the sum of the cheapest
+// edges entering the unvisited groups
+fn branch_and_bound<DistFunT: Fn(usize, usize) -> f64>(groups: &[Vec<usize>], dist: &DistFunT, initial: (Vec<GroupVert>, f64)) -> (Vec<GroupVert>, f64) {
+    let start_group = (0..groups.len()).min_by_key(|g| groups[*g].len()).unwrap();
+    let min_in: Vec<f64> = (0..groups.len()).map(|g| {
+        let mut min = f64::INFINITY;
+        for h in 0..groups.len() {
+            if h == g {
+                continue;
+            }
+            for u in &groups[h] {
+                for v in &groups[g] {
+                    min = min.min(dist(*u, *v));
+                }
+            }
+        }
+        if min == f64::INFINITY { 0.0 } else { min }
+    }).collect();
+    let mut bnb = BranchAndBound {
+        groups,
+        dist,
+        min_in,
+        visited: vec![false; groups.len()],
+        path: Vec::with_capacity(groups.len()),
+        best: initial
+    };
+    let remaining_bound: f64 = bnb.min_in.iter().sum();
+    bnb.visited[start_group] = true;
+    for start in 0..groups[start_group].len() {
+        bnb.path.push(GroupVert { group: start_group, vert: start });
+        bnb.search(0.0, remaining_bound);
+        bnb.path.pop();
+    }
+    bnb.best
+}
+
+fn gtsp_dist(problem: &GtspProblem) -> impl Fn(usize, usize) -> f64 + '_ {
+    |u, v| problem.distances.get(u, v)
+}
+
+pub fn gtsp_held_karp(problem: Rc<GtspProblem>) -> BSFSingleObjSolution<GtspPermutation> {
+    let (perm, fitness) = held_karp(&problem.groups, &gtsp_dist(&problem));
+    BSFSingleObjSolution { value: GtspPermutation { spec: problem, perm }, fitness }
+}
+
+// starts from the best of a few cheapest insertion tours
+pub fn gtsp_branch_and_bound(problem: Rc<GtspProblem>) -> BSFSingleObjSolution<GtspPermutation> {
+    let fitness = GtspFitness {};
+    let mut initial = (Vec::<GroupVert>::new(), f64::INFINITY);
+    for _ in 0..4 {
+        let perm = GtspPermutation { spec: problem.clone(), perm: gtsp_cheapest_insertion(&problem) };
+        let len = fitness.eval(&perm);
+        if len < initial.1 {
+            initial = (perm.perm, len);
+        }
+    }
+    let (perm, fitness) = branch_and_bound(&problem.groups, &gtsp_dist(&problem), initial);
+    BSFSingleObjSolution { value: GtspPermutation { spec: problem, perm }, fitness }
+}
+
+pub fn gtsp_held_karp_fits(problem: &GtspProblem) -> bool {
+    let start_group_size = problem.groups.iter().map(|group| group.len()).min().unwrap();
+    let states = (1usize << (problem.groups.len() - 1).min(usize::BITS as usize - 1)).saturating_mul(problem.vert_count - start_group_size);
+    states <= HELD_KARP_MAX_STATES
+}
+
+// Held-Karp if it fits into HELD_KARP_MAX_STATES, branch and bound otherwise, which can take
+// very long for many groups
+pub fn gtsp_exact(problem: Rc<GtspProblem>) -> BSFSingleObjSolution<GtspPermutation> {
+    if gtsp_held_karp_fits(&problem) {
+        gtsp_held_karp(problem)
+    } else {
+        gtsp_branch_and_bound(problem)
+    }
+}
+
+fn tsp_groups(vert_count: usize) -> Vec<Vec<usize>> {
+    (0..vert_count).map(|v| vec![v]).collect()
+}
+
+fn tsp_solution<DistanceMatrixT: DistanceMatrix>(distances: &DistanceMatrixT, perm: Vec<GroupVert>) -> BSFSingleObjSolution<TspPermutation> {
+    let vert_perm: Vec<usize> = perm.iter().map(|gv| gv.group).collect();
+    let fitness = tour_len(&vert_perm, &|u: &usize, v: &usize| distances.get(*u, *v));
+    BSFSingleObjSolution { value: TspPermutation { vert_perm }, fitness }
+}
+
+pub fn tsp_held_karp<DistanceMatrixT: DistanceMatrix>(distances: &DistanceMatrixT) -> BSFSingleObjSolution<TspPermutation> {
+    let (perm, _) = held_karp(&tsp_groups(distances.get_vert_count()), &|u, v| distances.get(u, v));
+    tsp_solution(distances, perm)
+}
+
+pub fn tsp_branch_and_bound<DistanceMatrixT: DistanceMatrix>(distances: &DistanceMatrixT) -> BSFSingleObjSolution<TspPermutation> {
+    let n = distances.get_vert_count();
+    let initial: Vec<GroupVert> = (0..n).map(|v| GroupVert { group: v, vert: 0 }).collect();
+    let initial_len = tour_len(&initial, &|u: &GroupVert, v: &GroupVert| distances.get(u.group, v.group));
+    let (perm, _) = branch_and_bound(&tsp_groups(n), &|u, v| distances.get(u, v), (initial, initial_len));
+    tsp_solution(distances, perm)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_exact_solvers() {
+        let (problem, _) = gen_euclidean_gtsp_problem(40, 8);
+        let problem = Rc::new(problem);
+        let fitness = GtspFitness {};
+        let held_karp = gtsp_held_karp(problem.clone());
+        let bnb = gtsp_branch_and_bound(problem.clone());
+        assert!((held_karp.fitness - bnb.fitness).abs() < 1e-9);
+        assert!((held_karp.fitness - fitness.eval(&held_karp.value)).abs() < 1e-9);
+        assert!((bnb.fitness - fitness.eval(&bnb.value)).abs() < 1e-9);
+        // heuristics can't beat the optimum
+        let local_search_perturbation = CombineMovePerturbeMutOps { mut_ops: vec![
+            ProbMovePerturbeMutOp { prob: 0.5, op: Rc::from(GtspRandGroupVertPerturbation::new(problem.groups.len()))},
+            ProbMovePerturbeMutOp { prob: 0.5, op: Rc::from(GtspReverseGroupPerturbation {})}
+        ]};
+        let (sol, _) = local_search_delta(
+            &mut GtspFitness {},
+            InitRandomGtspPopulation { spec: problem.clone(), size: 1 },
+            local_search_perturbation,
+            &MaxIterTerminationCond { n_iters: 2000 });
+        assert!(sol.fitness >= held_karp.fitness - 1e-9);
+
+        let positions: Vec<[f64; 2]> = (0..9).map(|_| [rand::random::<f64>() * 100.0, rand::random::<f64>() * 100.0]).collect();
+        let distances = vert_positions_to_distances(&positions);
+        let held_karp = tsp_held_karp(&distances);
+        let bnb = tsp_branch_and_bound(&distances);
+        assert!((held_karp.fitness - bnb.fitness).abs() < 1e-9);
+        let mut sorted = held_karp.value.vert_perm.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..9).collect::<Vec<usize>>());
+    }
+}
@@ -3,6 +3,7 @@ use std::cell::RefCell;
 use crate::opt_traits::*;
 use crate::opt_data::*;
 use crate::utils::*;
+use crate::bit_array::*;
 
 pub fn naive_one_max(bits: &[u8]) -> i32 {
     let mut one_count: i32 = 0;
@@ -104,19 +105,41 @@ pub struct LabsFunc { }
 pub struct NaiveBitRealFunc<RealFunc: FitnessFunc<FloatVec>> {
     pub real_func: RealFunc,
     pub bounds: Vec<Bounds>,
+    pub encoding: BinaryEncoding,
+    // bits of every variable, when empty the bits are split evenly
+    pub bits_counts: Vec<usize>,
     temp_data: RefCell<FloatVec>
 }
 
 impl<RealFunc: FitnessFunc<FloatVec>> NaiveBitRealFunc<RealFunc> {
     pub fn new(real_func: RealFunc, bounds: Vec<Bounds>) -> Self {
+        Self::new_with_encoding(real_func, bounds, BinaryEncoding::Binary, Vec::new())
+    }
+
+    pub fn new_with_encoding(real_func: RealFunc, bounds: Vec<Bounds>, encoding: BinaryEncoding, bits_counts: Vec<usize>) -> Self {
         let len = bounds.len();
-        NaiveBitRealFunc { real_func: real_func, bounds: bounds, temp_data: RefCell::new(FloatVec { values: vec![0.0; len] }) }
+        NaiveBitRealFunc { real_func, bounds, encoding, bits_counts, temp_data: RefCell::new(FloatVec { values: vec![0.0; len] }) }
+    }
+
+    pub fn decode(&self, data: &NaiveBitVec) -> FloatVec {
+        let mut res = FloatVec { values: Vec::with_capacity(self.bounds.len()) };
+        self.decode_mut(data, &mut res);
+        res
+    }
+
+    fn decode_mut(&self, data: &NaiveBitVec, res: &mut FloatVec) {
+        if self.bits_counts.is_empty() {
+            let bits_counts = bits_uniform(data.bits.len(), self.bounds.len());
+            bin_to_real_encoded_mut(&data.bits, &self.bounds, &bits_counts, self.encoding, &mut res.values);
+        } else {
+            bin_to_real_encoded_mut(&data.bits, &self.bounds, &self.bits_counts, self.encoding, &mut res.values);
+        }
     }
 }
 
 impl<RealFunc: FitnessFunc<FloatVec>> FitnessFunc<NaiveBitVec> for NaiveBitRealFunc<RealFunc> {
     fn eval(&self, data: &NaiveBitVec) -> f64 {
-        bin_to_real_mut(&data.bits, &self.bounds, &mut self.temp_data.borrow_mut().values);
+        self.decode_mut(data, &mut self.temp_data.borrow_mut());
         self.real_func.eval(&self.temp_data.borrow())
     }
 }
@@ -155,6 +178,18 @@ impl FitnessFunc<NaiveBitVec> for LabsFunc {
     }
 }
 
+impl FitnessFunc<BitVec> for OneMaxFunc {
+    fn eval(&self, data: &BitVec) -> f64 {
+        one_max(data) as f64
+    }
+}
+
+impl FitnessFunc<BitVec> for LabsFunc {
+    fn eval(&self, data: &BitVec) -> f64 {
+        labs(data) as f64
+    }
+}
+
 impl FitnessFunc<FloatVec> for SphereFunc {
     fn eval(&self, data: &FloatVec) -> f64 {
         sphere(&data.values, &self.o)
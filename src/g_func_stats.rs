@@ -115,7 +115,7 @@ pub fn create_g_funcs_comparison_graphs(num_repetitions: usize, num_iters: usize
         let mut multi_obj_transformer = NSGA2FitnessTransformer::new();
 
         let init_population = InitRandomFloatVecPopulation {
-            size: population_size ,vec_size: g_fitness.vec_size(), mean: mean, std_dev: 0.3 * val_range, bounds: bounds.clone()
+            size: population_size ,vec_size: g_fitness.vec_size(), mean: mean, std_dev: 0.3 * val_range, bounds: bounds.clone(), bound_handling: BoundHandling::Clamp
         };
 
         let mut avg_fitness_stats = vec![
@@ -0,0 +1,559 @@
+use std::{cell::Cell, fs::File, io::{BufRead, BufReader}, rc::Rc};
+
+use rand::Rng;
+
+use crate::opt_traits::*;
+use crate::opt_data::*;
+use crate::termination::*;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GpFunction {
+    Add,
+    Sub,
+    Mul,
+    // protected division, returns 1 for a zero denominator
+    Div,
+    Sin,
+    Cos,
+    // protected exponential, the argument is limited to keep the value finite
+    Exp,
+    // protected logarithm of the absolute value, returns 0 for zero
+    Log
+}
+
+impl GpFunction {
+    pub fn arity(&self) -> usize {
+        match self {
+            GpFunction::Add | GpFunction::Sub | GpFunction::Mul | GpFunction::Div => 2,
+            GpFunction::Sin | GpFunction::Cos | GpFunction::Exp | GpFunction::Log => 1
+        }
+    }
+
+    pub fn apply(&self, args: &[f64]) -> f64 {
+        match self {
+            GpFunction::Add => args[0] + args[1],
+            GpFunction::Sub => args[0] - args[1],
+            GpFunction::Mul => args[0] * args[1],
+            GpFunction::Div => if args[1].abs() < 1.0e-9 { 1.0 } else { args[0] / args[1] },
+            GpFunction::Sin => args[0].sin(),
+            GpFunction::Cos => args[0].cos(),
+            GpFunction::Exp => args[0].min(100.0).exp(),
+            GpFunction::Log => if args[0].abs() < 1.0e-9 { 0.0 } else { args[0].abs().ln() }
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            GpFunction::Add => "+",
+            GpFunction::Sub => "-",
+            GpFunction::Mul => "*",
+            GpFunction::Div => "/",
+            GpFunction::Sin => "sin",
+            GpFunction::Cos => "cos",
+            GpFunction::Exp => "exp",
+            GpFunction::Log => "log"
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GpNode {
+    Func(GpFunction),
+    // index of the input variable
+    Var(usize),
+    Const(f64)
+}
+
+impl GpNode {
+    pub fn arity(&self) -> usize {
+        match self {
+            GpNode::Func(func) => func.arity(),
+            GpNode::Var(_) | GpNode::Const(_) => 0
+        }
+    }
+}
+
+// Function and terminal sets with the limits of the trees
+pub struct GpSpec {
+    pub functions: Vec<GpFunction>,
+    pub vars_count: usize,
+    // ephemeral random constants are drawn from the bounds, None disables constants
+    pub const_bounds: Option<Bounds>,
+    // depth of a single node tree is 0
+    pub max_depth: usize,
+    pub max_size: usize
+}
+
+impl GpSpec {
+    pub fn random_terminal(&self) -> GpNode {
+        let mut rng = rand::thread_rng();
+        match self.const_bounds {
+            Some(bounds) if rng.gen_range(0..=self.vars_count) == self.vars_count => GpNode::Const(rng.gen_range(bounds.lower..=bounds.upper)),
+            _ => GpNode::Var(rng.gen_range(0..self.vars_count))
+        }
+    }
+
+    pub fn random_function(&self) -> GpNode {
+        GpNode::Func(self.functions[rand::thread_rng().gen_range(0..self.functions.len())])
+    }
+
+    // function of the same arity as node, terminals are replaced by random terminals
+    fn random_same_arity(&self, node: GpNode) -> GpNode {
+        let arity = node.arity();
+        if arity == 0 {
+            return self.random_terminal();
+        }
+        let same_arity: Vec<GpFunction> = self.functions.iter().copied().filter(|func| func.arity() == arity).collect();
+        GpNode::Func(same_arity[rand::thread_rng().gen_range(0..same_arity.len())])
+    }
+
+    fn terminals_count(&self) -> usize {
+        self.vars_count + self.const_bounds.is_some() as usize
+    }
+
+    // appends a random tree in prefix order, full trees have all leaves at the given depth,
+    // grown trees choose from all nodes and stop at the given depth
+    pub fn gen_tree(&self, depth: usize, full: bool, nodes: &mut Vec<GpNode>) {
+        let func_prob = self.functions.len() as f64 / (self.functions.len() + self.terminals_count()) as f64;
+        if depth == 0 || (!full && rand::random::<f64>() >= func_prob) {
+            nodes.push(self.random_terminal());
+            return;
+        }
+        let func = self.random_function();
+        nodes.push(func);
+        for _ in 0..func.arity() {
+            self.gen_tree(depth - 1, full, nodes);
+        }
+    }
+}
+
+// Expression tree stored in prefix order
+#[derive(Clone, Debug)]
+pub struct GpTree {
+    pub nodes: Vec<GpNode>
+}
+
+impl OptData for GpTree {
+    fn dim(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl TerminationCond<GpTree> for MaxIterTerminationCond {
+    fn eval(&self, iter: usize, _: f64) -> bool {
+        return iter >= self.n_iters;
+    }
+}
+
+impl GpTree {
+    pub fn random(spec: &GpSpec, depth: usize, full: bool) -> Self {
+        let mut nodes = Vec::new();
+        spec.gen_tree(depth, full, &mut nodes);
+        GpTree { nodes }
+    }
+
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // index after the last node of the subtree rooted at start
+    pub fn subtree_end(&self, start: usize) -> usize {
+        let mut open = 1;
+        let mut i = start;
+        while open > 0 {
+            open = open + self.nodes[i].arity() - 1;
+            i += 1;
+        }
+        i
+    }
+
+    pub fn depth(&self) -> usize {
+        // stack of the remaining children counts of the open nodes
+        let mut remaining = Vec::<usize>::new();
+        let mut depth = 0;
+        for node in &self.nodes {
+            depth = depth.max(remaining.len());
+            if node.arity() > 0 {
+                remaining.push(node.arity());
+                continue;
+            }
+            while let Some(last) = remaining.last_mut() {
+                *last -= 1;
+                if *last > 0 {
+                    break;
+                }
+                remaining.pop();
+            }
+        }
+        depth
+    }
+
+    pub fn satisfies_limits(&self, spec: &GpSpec) -> bool {
+        self.size() <= spec.max_size && self.depth() <= spec.max_depth
+    }
+
+    // copy of the tree with the subtree rooted at start replaced
+    pub fn replace_subtree(&self, start: usize, subtree: &[GpNode]) -> GpTree {
+        let end = self.subtree_end(start);
+        let mut nodes = Vec::with_capacity(self.nodes.len() - (end - start) + subtree.len());
+        nodes.extend_from_slice(&self.nodes[..start]);
+        nodes.extend_from_slice(subtree);
+        nodes.extend_from_slice(&self.nodes[end..]);
+        GpTree { nodes }
+    }
+
+    // random node index, function nodes are chosen with func_prob when the tree has any
+    pub fn random_point(&self, func_prob: f64) -> usize {
+        let mut rng = rand::thread_rng();
+        let want_func = rng.gen::<f64>() < func_prob;
+        let candidates: Vec<usize> = (0..self.nodes.len()).filter(|i| (self.nodes[*i].arity() > 0) == want_func).collect();
+        if candidates.is_empty() {
+            rng.gen_range(0..self.nodes.len())
+        } else {
+            candidates[rng.gen_range(0..candidates.len())]
+        }
+    }
+
+    fn eval_at(&self, index: usize, vars: &[f64]) -> (f64, usize) {
+        match self.nodes[index] {
+            GpNode::Var(var) => (vars[var], index + 1),
+            GpNode::Const(value) => (value, index + 1),
+            GpNode::Func(func) => {
+                let mut args = [0.0; 2];
+                let mut next = index + 1;
+                for arg in args.iter_mut().take(func.arity()) {
+                    (*arg, next) = self.eval_at(next, vars);
+                }
+                (func.apply(&args), next)
+            }
+        }
+    }
+
+    pub fn eval(&self, vars: &[f64]) -> f64 {
+        self.eval_at(0, vars).0
+    }
+
+    fn expr_string_at(&self, index: usize, res: &mut String) -> usize {
+        match self.nodes[index] {
+            GpNode::Var(var) => { res.push_str(&format!("x{}", var)); index + 1 },
+            GpNode::Const(value) => { res.push_str(&format!("{:.4}", value)); index + 1 },
+            GpNode::Func(func) if func.arity() == 2 => {
+                res.push('(');
+                let next = self.expr_string_at(index + 1, res);
+                res.push_str(&format!(" {} ", func.symbol()));
+                let next = self.expr_string_at(next, res);
+                res.push(')');
+                next
+            },
+            GpNode::Func(func) => {
+                res.push_str(func.symbol());
+                res.push('(');
+                let next = self.expr_string_at(index + 1, res);
+                res.push(')');
+                next
+            }
+        }
+    }
+
+    // infix form of the expression, variables are named x0, x1, ...
+    pub fn expr_string(&self) -> String {
+        let mut res = String::new();
+        self.expr_string_at(0, &mut res);
+        res
+    }
+}
+
+// Ramped half-and-half, depths are cycled through min_depth..=max_depth and
+// every other tree of each depth is full, the rest is grown.
+// Trees exceeding the limits are generated again by grow.
+#[derive(Clone)]
+pub struct InitRampedHalfAndHalfGpPopulation {
+    pub size: usize,
+    pub spec: Rc<GpSpec>,
+    pub min_depth: usize,
+    pub max_depth: usize
+}
+
+impl InitRampedHalfAndHalfGpPopulation {
+    fn gen_tree(&self, depth: usize, full: bool) -> GpTree {
+        let mut tree = GpTree::random(&self.spec, depth, full);
+        while !tree.satisfies_limits(&self.spec) {
+            tree = GpTree::random(&self.spec, depth, false);
+        }
+        tree
+    }
+}
+
+impl InitFunc<GpTree> for InitRampedHalfAndHalfGpPopulation {
+    fn init(&self) -> GpTree {
+        let depth = rand::thread_rng().gen_range(self.min_depth..=self.max_depth);
+        self.gen_tree(depth, rand::random::<bool>())
+    }
+}
+
+impl InitPopulation<GpTree> for InitRampedHalfAndHalfGpPopulation {
+    fn init(&self) -> Vec<GpTree> {
+        let depths_count = self.max_depth - self.min_depth + 1;
+        (0..self.size)
+            .map(|i| self.gen_tree(self.min_depth + (i / 2) % depths_count, i % 2 == 0))
+            .collect()
+    }
+}
+
+// Swaps random subtrees of the parents, offsprings exceeding the limits are replaced by their parent
+pub struct GpSubtreeCrossover {
+    pub spec: Rc<GpSpec>,
+    // probability of choosing a function node as the crossover point, Koza uses 0.9
+    pub func_point_prob: f64
+}
+
+impl GpSubtreeCrossover {
+    fn crossover_pair(&self, parents: [&GpTree; 2]) -> [GpTree; 2] {
+        let point1 = parents[0].random_point(self.func_point_prob);
+        let point2 = parents[1].random_point(self.func_point_prob);
+        let subtree1 = &parents[0].nodes[point1..parents[0].subtree_end(point1)];
+        let subtree2 = &parents[1].nodes[point2..parents[1].subtree_end(point2)];
+        let mut offsprings = [parents[0].replace_subtree(point1, subtree2), parents[1].replace_subtree(point2, subtree1)];
+        for o in 0..2 {
+            if !offsprings[o].satisfies_limits(&self.spec) {
+                offsprings[o] = parents[o].clone();
+            }
+        }
+        offsprings
+    }
+}
+
+impl Crossover<GpTree> for GpSubtreeCrossover {
+    fn crossover(&self, population: &Vec<GpTree>, parents_indices: &Vec<usize>, offsprings: &mut Vec<GpTree>) {
+        offsprings.clear();
+        for i in (0..parents_indices.len()).step_by(2) {
+            if i + 1 >= parents_indices.len() {
+                continue;
+            }
+            let parent1 = &population[parents_indices[i]];
+            let parent2 = &population[parents_indices[i + 1]];
+            let [offspring1, offspring2] = self.crossover_pair([parent1, parent2]);
+            offsprings.push(offspring1);
+            offsprings.push(offspring2);
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum GpMutationType {
+    // replaces a random subtree by a grown tree of at most max_depth
+    Subtree { max_depth: usize },
+    // replaces every node with probability 1/size by a node of the same arity
+    Point,
+    // replaces a random subtree by one of its own subtrees
+    Hoist
+}
+
+// Mutations exceeding the limits are discarded
+#[derive(Clone)]
+pub struct GpPerturbeMutOp {
+    pub spec: Rc<GpSpec>,
+    pub mutation: GpMutationType
+}
+
+impl PerturbeMutOp<GpTree> for GpPerturbeMutOp {
+    fn eval(&self, data: &mut GpTree) {
+        let mut rng = rand::thread_rng();
+        match self.mutation {
+            GpMutationType::Subtree { max_depth } => {
+                let point = rng.gen_range(0..data.size());
+                let mut subtree = Vec::new();
+                self.spec.gen_tree(rng.gen_range(0..=max_depth), false, &mut subtree);
+                let mutated = data.replace_subtree(point, &subtree);
+                if mutated.satisfies_limits(&self.spec) {
+                    *data = mutated;
+                }
+            },
+            GpMutationType::Point => {
+                let node_prob = 1.0 / data.size() as f64;
+                for node in data.nodes.iter_mut() {
+                    if rng.gen::<f64>() < node_prob {
+                        *node = self.spec.random_same_arity(*node);
+                    }
+                }
+            },
+            GpMutationType::Hoist => {
+                let point = rng.gen_range(0..data.size());
+                let inner_point = rng.gen_range(point..data.subtree_end(point));
+                let subtree = data.nodes[inner_point..data.subtree_end(inner_point)].to_vec();
+                *data = data.replace_subtree(point, &subtree);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum GpBloatControl {
+    None,
+    // errors are rounded to precision and equal errors are ordered by the tree size
+    Lexicographic { precision: f64 },
+    // trees larger than the average evaluated size get the worst fitness with probability prob
+    Tarpeian { prob: f64 }
+}
+
+// Root mean squared error of the tree on a dataset, var i of the tree is the column i of inputs
+pub struct SymbolicRegressionFunc {
+    pub inputs: Vec<Vec<f64>>,
+    pub targets: Vec<f64>,
+    pub bloat_control: GpBloatControl,
+    pub max_size: usize,
+    // exponential moving average of the evaluated sizes used by Tarpeian control
+    mean_size: Cell<f64>
+}
+
+impl SymbolicRegressionFunc {
+    pub fn new(inputs: Vec<Vec<f64>>, targets: Vec<f64>, bloat_control: GpBloatControl, max_size: usize) -> Self {
+        SymbolicRegressionFunc { inputs, targets, bloat_control, max_size, mean_size: Cell::new(0.0) }
+    }
+
+    // comma separated values, the last column is the target and an optional header line is skipped
+    pub fn from_csv(file_path: &str, bloat_control: GpBloatControl, max_size: usize) -> Self {
+        let file = File::open(file_path).expect("file wasn't found.");
+        let mut inputs = Vec::new();
+        let mut targets = Vec::new();
+        for (line_index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.unwrap();
+            if line.trim().is_empty() {
+                continue;
+            }
+            let values: Result<Vec<f64>, _> = line.split(',').map(|value| value.trim().parse::<f64>()).collect();
+            match values {
+                Ok(mut values) => {
+                    targets.push(values.pop().expect("empty row"));
+                    inputs.push(values);
+                },
+                Err(_) if line_index == 0 => continue,
+                Err(err) => panic!("invalid value on line {}: {}", line_index + 1, err)
+            }
+        }
+        Self::new(inputs, targets, bloat_control, max_size)
+    }
+
+    pub fn vars_count(&self) -> usize {
+        self.inputs.first().map_or(0, |row| row.len())
+    }
+
+    pub fn error(&self, tree: &GpTree) -> f64 {
+        let mut sum = 0.0;
+        for i in 0..self.inputs.len() {
+            let diff = tree.eval(&self.inputs[i]) - self.targets[i];
+            sum += diff * diff;
+        }
+        let error = (sum / self.inputs.len() as f64).sqrt();
+        if error.is_finite() { error } else { f64::MAX }
+    }
+}
+
+impl FitnessFunc<GpTree> for SymbolicRegressionFunc {
+    fn eval(&self, data: &GpTree) -> f64 {
+        let error = self.error(data);
+        match self.bloat_control {
+            GpBloatControl::None => error,
+            GpBloatControl::Lexicographic { precision } => {
+                if error == f64::MAX {
+                    return error;
+                }
+                // size term stays below half of the precision so it only breaks ties
+                (error / precision).round() * precision + data.size() as f64 * precision / (2.0 * (self.max_size + 1) as f64)
+            },
+            GpBloatControl::Tarpeian { prob } => {
+                let size = data.size() as f64;
+                let mean_size = if self.mean_size.get() == 0.0 { size } else { self.mean_size.get() };
+                self.mean_size.set(0.99 * mean_size + 0.01 * size);
+                if size > mean_size && rand::random::<f64>() < prob { f64::MAX } else { error }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::opt_search::*;
+    use crate::selection::*;
+    use crate::replacement::*;
+
+    fn spec() -> Rc<GpSpec> {
+        Rc::new(GpSpec {
+            functions: vec![GpFunction::Add, GpFunction::Sub, GpFunction::Mul, GpFunction::Div, GpFunction::Sin],
+            vars_count: 1,
+            const_bounds: Some(Bounds { lower: -1.0, upper: 1.0 }),
+            max_depth: 8,
+            max_size: 60
+        })
+    }
+
+    #[test]
+    fn test_eval_and_depth() {
+        // x0 * x0 + sin(x0)
+        let tree = GpTree { nodes: vec![GpNode::Func(GpFunction::Add), GpNode::Func(GpFunction::Mul), GpNode::Var(0), GpNode::Var(0),
+            GpNode::Func(GpFunction::Sin), GpNode::Var(0)] };
+        assert_eq!(tree.eval(&[2.0]), 4.0 + 2.0f64.sin());
+        assert_eq!(tree.depth(), 2);
+        assert_eq!(tree.subtree_end(1), 4);
+        assert_eq!(tree.expr_string(), "((x0 * x0) + sin(x0))");
+    }
+
+    #[test]
+    fn test_operators_keep_limits() {
+        let spec = spec();
+        let init = InitRampedHalfAndHalfGpPopulation { size: 20, spec: spec.clone(), min_depth: 2, max_depth: 6 };
+        let population = InitPopulation::<GpTree>::init(&init);
+        let parents_indices: Vec<usize> = (0..population.len()).collect();
+        let crossover = GpSubtreeCrossover { spec: spec.clone(), func_point_prob: 0.9 };
+        let mutations = [GpMutationType::Subtree { max_depth: 4 }, GpMutationType::Point, GpMutationType::Hoist];
+        let mut offsprings = Vec::new();
+        // the unpaired last parent is skipped
+        crossover.crossover(&population, &parents_indices[..19].to_vec(), &mut offsprings);
+        assert_eq!(offsprings.len(), 18);
+        for _ in 0..20 {
+            crossover.crossover(&population, &parents_indices, &mut offsprings);
+            for offspring in offsprings.iter_mut() {
+                for mutation in mutations {
+                    GpPerturbeMutOp { spec: spec.clone(), mutation }.eval(offspring);
+                }
+                assert!(offspring.satisfies_limits(&spec));
+                assert_eq!(offspring.subtree_end(0), offspring.size());
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_csv() {
+        let func = SymbolicRegressionFunc::from_csv("data/symreg/quartic.csv", GpBloatControl::None, 60);
+        assert_eq!(func.vars_count(), 1);
+        assert_eq!(func.inputs.len(), 21);
+        let x = GpNode::Var(0);
+        let mul = GpNode::Func(GpFunction::Mul);
+        let add = GpNode::Func(GpFunction::Add);
+        let div = GpNode::Func(GpFunction::Div);
+        // x (x / x + x (x / x + x (x / x + x)))
+        let quartic = GpTree { nodes: vec![mul, x, add, div, x, x, mul, x, add, div, x, x, mul, x, add, div, x, x, x] };
+        assert!(func.error(&quartic) < 1.0e-5);
+    }
+
+    #[test]
+    fn test_symbolic_regression_search() {
+        let spec = spec();
+        let mut func = SymbolicRegressionFunc::from_csv("data/symreg/quartic.csv", GpBloatControl::None, spec.max_size);
+        let init = InitRampedHalfAndHalfGpPopulation { size: 100, spec: spec.clone(), min_depth: 2, max_depth: 6 };
+        let (solution, stats): (BSFSingleObjSolution<GpTree>, BSFSingleObjStatistics) = evolutionary_search(
+            &mut func,
+            init,
+            &TournamentSelection { select_count: 100, rounds_count: 4 },
+            &GpSubtreeCrossover { spec: spec.clone(), func_point_prob: 0.9 },
+            GpPerturbeMutOp { spec: spec.clone(), mutation: GpMutationType::Subtree { max_depth: 4 } },
+            &TruncationReplacementStrategy {},
+            &MaxIterTerminationCond { n_iters: 30 },
+            false);
+        assert!(stats.fitness.last().unwrap() < stats.fitness.first().unwrap());
+        assert!(solution.value.satisfies_limits(&spec));
+        assert!((func.error(&solution.value) - solution.fitness).abs() < 1.0e-9);
+    }
+}
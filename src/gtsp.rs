@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 use rand::Rng;
 
 use crate::*;
@@ -8,7 +8,7 @@ pub struct GtspProblem {
     pub vert_count: usize,
     pub best_known: f64,
     pub groups: Vec<Vec<usize>>,
-    pub distances: DistanceHalfMatrix
+    pub distances: Distances
 }
 
 #[derive(Copy, Clone)]
@@ -23,6 +23,9 @@ impl SameVertex for GroupVert {
     fn is_same(&self, other: &Self) -> bool {
         self.group == other.group
     }
+    fn id(&self) -> usize {
+        self.group
+    }
 }
 
 #[derive(Clone)]
@@ -60,6 +63,42 @@ impl TerminationCond<GtspPermutation> for MaxIterTerminationCond {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GtspMove {
+    // move of the groups order
+    Perm(PermMove),
+    // chooses the local vertex vert in the group on the position pos
+    ChangeVert { pos: usize, vert: usize }
+}
+
+pub fn apply_gtsp_move(data: &mut GtspPermutation, mv: &GtspMove) {
+    match mv {
+        GtspMove::Perm(perm_move) => apply_perm_move(&mut data.perm, perm_move),
+        GtspMove::ChangeVert { pos, vert } => data.perm[*pos].vert = *vert
+    }
+}
+
+impl MoveFitnessFunc<GtspPermutation, GtspMove> for GtspFitness {
+    fn move_delta(&self, data: &GtspPermutation, mv: &GtspMove) -> f64 {
+        let spec = &data.spec;
+        let dist = |u: &GroupVert, v: &GroupVert| spec.distances.get(spec.groups[u.group][u.vert], spec.groups[v.group][v.vert]);
+        match mv {
+            GtspMove::Perm(perm_move) => perm_move_delta(&data.perm, perm_move, spec.distances.is_symmetric(), &dist),
+            GtspMove::ChangeVert { pos, vert } => {
+                let n = data.perm.len();
+                let prev = &data.perm[(pos + n - 1) % n];
+                let next = &data.perm[(pos + 1) % n];
+                let old = &data.perm[*pos];
+                let new = GroupVert { group: old.group, vert: *vert };
+                if n == 1 {
+                    return 0.0;
+                }
+                dist(prev, &new) + dist(&new, next) - dist(prev, old) - dist(old, next)
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct InitRandomGtspPopulation {
     pub spec: Rc<GtspProblem>,
@@ -119,6 +158,14 @@ impl PerturbeMutOp<GtspPermutation> for GtspMoveGroupPerturbation {
     }
 }
 
+impl MovePerturbeMutOp<GtspPermutation, GtspMove> for GtspMoveGroupPerturbation {
+    fn eval_moves(&self, data: &mut GtspPermutation, on_move: &mut dyn FnMut(&GtspPermutation, &GtspMove)) {
+        let mv = GtspMove::Perm(PermMove::random_move(data.perm.len()));
+        on_move(data, &mv);
+        apply_gtsp_move(data, &mv);
+    }
+}
+
 #[derive(Clone)]
 pub struct GtspSwapGroupPerturbation {
 }
@@ -129,6 +176,14 @@ impl PerturbeMutOp<GtspPermutation> for GtspSwapGroupPerturbation {
     }
 }
 
+impl MovePerturbeMutOp<GtspPermutation, GtspMove> for GtspSwapGroupPerturbation {
+    fn eval_moves(&self, data: &mut GtspPermutation, on_move: &mut dyn FnMut(&GtspPermutation, &GtspMove)) {
+        let mv = GtspMove::Perm(PermMove::random_swap(data.perm.len()));
+        on_move(data, &mv);
+        apply_gtsp_move(data, &mv);
+    }
+}
+
 #[derive(Clone)]
 pub struct GtspReverseGroupPerturbation {
 }
@@ -139,6 +194,14 @@ impl PerturbeMutOp<GtspPermutation> for GtspReverseGroupPerturbation {
     }
 }
 
+impl MovePerturbeMutOp<GtspPermutation, GtspMove> for GtspReverseGroupPerturbation {
+    fn eval_moves(&self, data: &mut GtspPermutation, on_move: &mut dyn FnMut(&GtspPermutation, &GtspMove)) {
+        let mv = GtspMove::Perm(PermMove::random_reverse(data.perm.len()));
+        on_move(data, &mv);
+        apply_gtsp_move(data, &mv);
+    }
+}
+
 #[derive(Clone)]
 pub struct GtspRandGroupVertPerturbation {
     // recommended to set to 1/number of groups
@@ -153,10 +216,18 @@ impl GtspRandGroupVertPerturbation {
 
 impl PerturbeMutOp<GtspPermutation> for GtspRandGroupVertPerturbation {
     fn eval(&self, data: &mut GtspPermutation) {
+        self.eval_moves(data, &mut |_, _| {});
+    }
+}
+
+impl MovePerturbeMutOp<GtspPermutation, GtspMove> for GtspRandGroupVertPerturbation {
+    fn eval_moves(&self, data: &mut GtspPermutation, on_move: &mut dyn FnMut(&GtspPermutation, &GtspMove)) {
         for i in 0..data.perm.len() {
             if rand::random::<f64>() < self.change_prob {
                 let group = data.perm[i].group;
-                data.perm[i].vert = rand::thread_rng().gen_range(0..data.spec.groups[group].len());
+                let mv = GtspMove::ChangeVert { pos: i, vert: rand::thread_rng().gen_range(0..data.spec.groups[group].len()) };
+                on_move(data, &mv);
+                apply_gtsp_move(data, &mv);
             }
         }
     }
@@ -176,6 +247,12 @@ impl GtspRouletteWheelGroupVertPerturbation {
 
 impl PerturbeMutOp<GtspPermutation> for GtspRouletteWheelGroupVertPerturbation {
     fn eval(&self, data: &mut GtspPermutation) {
+        self.eval_moves(data, &mut |_, _| {});
+    }
+}
+
+impl MovePerturbeMutOp<GtspPermutation, GtspMove> for GtspRouletteWheelGroupVertPerturbation {
+    fn eval_moves(&self, data: &mut GtspPermutation, on_move: &mut dyn FnMut(&GtspPermutation, &GtspMove)) {
         for i in 0..data.perm.len() {
             if rand::random::<f64>() < self.change_prob {
                 let group = data.perm[i].group;
@@ -198,7 +275,9 @@ impl PerturbeMutOp<GtspPermutation> for GtspRouletteWheelGroupVertPerturbation {
                     }
                     inv_dist_acc += 1.0 / data.spec.distances.get(vert, v);
                     if select_vert <= inv_dist_acc {
-                        data.perm[i].vert = vi;
+                        let mv = GtspMove::ChangeVert { pos: i, vert: vi };
+                        on_move(data, &mv);
+                        apply_gtsp_move(data, &mv);
                         break;
                     }
                 }
@@ -227,6 +306,22 @@ pub fn crossover_gtsp_data<CrossoverFunT : CrossoverFun<GroupVert>>
     }
 }
 
+pub fn multi_crossover_gtsp_data<MultiCrossoverFunT : MultiCrossoverFun<GroupVert>>
+    (population: &Vec<GtspPermutation>, parents_indices: &Vec<usize>, offsprings: &mut Vec<GtspPermutation>, crossover_fun: &MultiCrossoverFunT, crossover_prob: f64)
+{
+    offsprings.clear();
+    let parents: Vec<&Vec<GroupVert>> = parents_indices.iter().map(|i| &population[*i].perm).collect();
+    for perm in multi_crossover_vec(&parents, crossover_fun, crossover_prob) {
+        offsprings.push(GtspPermutation { spec: population[parents_indices[0]].spec.clone(), perm });
+    }
+}
+
+impl<MultiCrossoverFunT: MultiCrossoverFun<GroupVert>> Crossover<GtspPermutation> for MultiCrossover<MultiCrossoverFunT> {
+    fn crossover(&self, population: &Vec<GtspPermutation>, parents_indices: &Vec<usize>, offsprings: &mut Vec<GtspPermutation>) {
+        multi_crossover_gtsp_data(population, parents_indices, offsprings, &self.crossover_fun, self.crossover_prob);
+    }
+}
+
 pub fn gtsp_uniform_city_crossover(parents: [&Vec<GroupVert>; 2], offsprings: [&mut Vec<GroupVert>; 2]) {
     let mut group_indices = vec![[0usize;2]; parents[0].len()];
     for i in 0..parents[0].len() {
@@ -341,46 +436,150 @@ impl Crossover<GtspPermutation> for GtspGeneralCrossover {
     }
 }
 
+// Chooses between the crossovers of GtspGeneralCrossover for every pair of parents,
+// the reward is the improvement of the better offspring over the better parent. Crossovers don't get
+// the population fitness, so every pair costs four extra tour evaluations.
+pub struct AdaptiveGtspGeneralCrossover {
+    pub selector: Rc<RefCell<OperatorSelector>>
+}
+
+impl AdaptiveGtspGeneralCrossover {
+    pub const OPS_NAMES: [&'static str; 3] = ["city", "cycle", "order"];
+
+    pub fn new(method: OperatorSelectionMethod) -> Self {
+        AdaptiveGtspGeneralCrossover { selector: Rc::new(RefCell::new(OperatorSelector::new(method, Self::OPS_NAMES.len()))) }
+    }
+}
+
+impl Crossover<GtspPermutation> for AdaptiveGtspGeneralCrossover {
+    fn crossover(&self, population: &Vec<GtspPermutation>, parents_indices: &Vec<usize>, offsprings: &mut Vec<GtspPermutation>) {
+        offsprings.clear();
+        let fitness = GtspFitness {};
+        for i in (0..parents_indices.len()).step_by(2) {
+            if i + 1 >= parents_indices.len() {
+                continue;
+            }
+            let parent1 = &population[parents_indices[i]];
+            let parent2 = &population[parents_indices[i + 1]];
+            let mut offspring1 = GtspPermutation { spec: parent1.spec.clone(), perm: Vec::<GroupVert>::with_capacity(parent1.perm.len()) };
+            let mut offspring2 = GtspPermutation { spec: parent2.spec.clone(), perm: Vec::<GroupVert>::with_capacity(parent2.perm.len()) };
+            let op = self.selector.borrow().select();
+            let curr_parents = [&parent1.perm, &parent2.perm];
+            let curr_offsprings = [&mut offspring1.perm, &mut offspring2.perm];
+            match op {
+                0 => gtsp_uniform_city_crossover(curr_parents, curr_offsprings),
+                1 => tsp_cycle_crossover(curr_parents, curr_offsprings),
+                _ => tsp_order_crossover(curr_parents, curr_offsprings)
+            }
+            let parents_fitness = fitness.eval(parent1).min(fitness.eval(parent2));
+            let offsprings_fitness = fitness.eval(&offspring1).min(fitness.eval(&offspring2));
+            self.selector.borrow_mut().reward(op, improvement_reward(parents_fitness, offsprings_fitness));
+            offsprings.push(offspring1);
+            offsprings.push(offspring2);
+        }
+        self.selector.borrow_mut().record();
+    }
+}
+
+impl Crossover<GtspPermutation> for TspPmxCrossover {
+    fn crossover(&self, population: &Vec<GtspPermutation>, parents_indices: &Vec<usize>, offsprings: &mut Vec<GtspPermutation>) {
+        crossover_gtsp_data(population, parents_indices, offsprings, self);
+    }
+}
+
+impl Crossover<GtspPermutation> for TspPositionCrossover {
+    fn crossover(&self, population: &Vec<GtspPermutation>, parents_indices: &Vec<usize>, offsprings: &mut Vec<GtspPermutation>) {
+        crossover_gtsp_data(population, parents_indices, offsprings, self);
+    }
+}
+
+impl Crossover<GtspPermutation> for TspEdgeRecombinationCrossover {
+    fn crossover(&self, population: &Vec<GtspPermutation>, parents_indices: &Vec<usize>, offsprings: &mut Vec<GtspPermutation>) {
+        crossover_gtsp_data(population, parents_indices, offsprings, self);
+    }
+}
+
+// EAX on the group order, edge costs use the vertices chosen by the parent the offspring is built from
+pub struct GtspEaxCrossover {
+    pub spec: Rc<GtspProblem>,
+    pub strategy: EaxStrategy
+}
+
+impl CrossoverFun<GroupVert> for GtspEaxCrossover {
+    fn crossover_fun(&self, parents: [&Vec<GroupVert>; 2], offsprings: [&mut Vec<GroupVert>; 2]) {
+        let dist = |u: &GroupVert, v: &GroupVert| {
+            self.spec.distances.get(self.spec.groups[u.group][u.vert], self.spec.groups[v.group][v.vert])
+        };
+        tsp_eax_crossover(parents, offsprings, self.strategy, &dist);
+    }
+}
+
+impl Crossover<GtspPermutation> for GtspEaxCrossover {
+    fn crossover(&self, population: &Vec<GtspPermutation>, parents_indices: &Vec<usize>, offsprings: &mut Vec<GtspPermutation>) {
+        crossover_gtsp_data(population, parents_indices, offsprings, self);
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GtspConstruction {
+    NearestNeighbour,
+    // see gtsp_cheapest_insertion
+    CheapestInsertion
+}
+
 #[derive(Clone)]
 pub struct InitHeuristicGtspPopulation {
     pub spec: Rc<GtspProblem>,
-    pub size: usize
+    pub size: usize,
+    pub construction: GtspConstruction,
+    // fraction of the population built by the construction heuristic, the rest is random
+    pub heuristic_fraction: f64,
+    // nearest neighbour steps look into the candidate lists first and scan all groups
+    // only if all candidates are in visited groups
+    pub candidates: Option<Rc<CandidateLists<GroupVert>>>
 }
 
 impl InitHeuristicGtspPopulation {
     fn gen_perm(&self) -> GtspPermutation {
+        if self.construction == GtspConstruction::CheapestInsertion {
+            return GtspPermutation { spec: self.spec.clone(), perm: gtsp_cheapest_insertion(&self.spec) };
+        }
         let mut perm = GtspPermutation {
             spec: self.spec.clone(),
             perm: Vec::<GroupVert>::with_capacity(self.spec.groups.len())
         };
+        let mut group_visited = vec![false; self.spec.groups.len()];
         let start_group = rand::thread_rng().gen_range(0..self.spec.groups.len());
         let start_group_vert = rand::thread_rng().gen_range(0..self.spec.groups[start_group].len());
         perm.perm.push(GroupVert { group: start_group, vert: start_group_vert });
+        group_visited[start_group] = true;
         while perm.perm.len() < self.spec.groups.len() {
             let curr = perm.perm.last().unwrap();
             let curr_vert = self.spec.groups[curr.group][curr.vert];
-            let mut nearest = GroupVert { group: 0, vert: 0};
-            let mut nearest_dist = f64::INFINITY;
-            for g in 0..self.spec.groups.len() {
-                let mut g_visited = false;
-                for i in 0..perm.perm.len() {
-                    if g == perm.perm[i].group {
-                        g_visited = true;
-                        break;
-                    }
-                }
-                if g_visited {
-                    continue;
-                }
-                for v in 0..self.spec.groups[g].len() {
-                    let vert = self.spec.groups[g][v];
-                    let dist = self.spec.distances.get(curr_vert, vert);
-                    if dist < nearest_dist {
-                        nearest_dist = dist;
-                        nearest = GroupVert { group: g, vert: v};
+            let candidate = self.candidates.as_ref()
+                .and_then(|candidates| candidates.get(curr_vert).iter().find(|gv| !group_visited[gv.group]).copied());
+            let nearest = match candidate {
+                Some(gv) => gv,
+                None => {
+                    let mut nearest = GroupVert { group: 0, vert: 0};
+                    let mut nearest_dist = f64::INFINITY;
+                    for g in 0..self.spec.groups.len() {
+                        if group_visited[g] {
+                            continue;
+                        }
+                        for v in 0..self.spec.groups[g].len() {
+                            let vert = self.spec.groups[g][v];
+                            let dist = self.spec.distances.get(curr_vert, vert);
+                            if dist < nearest_dist {
+                                nearest_dist = dist;
+                                nearest = GroupVert { group: g, vert: v};
+                            }
+                        }
                     }
+                    nearest
                 }
-            }
+            };
+            group_visited[nearest.group] = true;
             perm.perm.push(nearest);
         }
         perm
@@ -389,7 +588,7 @@ impl InitHeuristicGtspPopulation {
 
 impl InitPopulation<GtspPermutation> for InitHeuristicGtspPopulation {
     fn init(&self) -> Vec<GtspPermutation> {
-        let heuristic_count = if self.size > 3 { self.size / 4 } else { self.size };
+        let heuristic_count = heuristic_count(self.size, self.heuristic_fraction);
         let rand_count = self.size - heuristic_count;
         let init_rand_population = InitRandomGtspPopulation { spec: self.spec.clone(), size: rand_count};
         let mut population = opt_traits::InitPopulation::init(&init_rand_population);
@@ -405,4 +604,43 @@ impl InitFunc<GtspPermutation> for InitHeuristicGtspPopulation {
     fn init(&self) -> GtspPermutation {
         self.gen_perm()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_move_delta_matches_eval() {
+        let (n, group_count) = (15, 5);
+        for symmetric in [true, false] {
+            let mut distances = Distances::new(n, symmetric);
+            for v1 in 0..n {
+                for v2 in 0..n {
+                    distances.set(v1, v2, if v1 == v2 { 0.0 } else { rand::random::<f64>() });
+                }
+            }
+            let groups: Vec<Vec<usize>> = (0..group_count).map(|g| (0..n).filter(|v| v % group_count == g).collect()).collect();
+            let spec = Rc::new(GtspProblem { vert_count: n, best_known: 1.0, groups, distances });
+            let fitness = GtspFitness {};
+            let mut perm: GtspPermutation = InitFunc::init(&InitRandomGtspPopulation { spec: spec.clone(), size: 1 });
+            for _ in 0..1000 {
+                let mv = match rand::thread_rng().gen_range(0..4) {
+                    0 => GtspMove::Perm(PermMove::random_move(group_count)),
+                    1 => GtspMove::Perm(PermMove::random_swap(group_count)),
+                    2 => GtspMove::Perm(PermMove::random_reverse(group_count)),
+                    _ => {
+                        let pos = rand::thread_rng().gen_range(0..group_count);
+                        let vert = rand::thread_rng().gen_range(0..spec.groups[perm.perm[pos].group].len());
+                        GtspMove::ChangeVert { pos, vert }
+                    }
+                };
+                let before = fitness.eval(&perm);
+                let delta = fitness.move_delta(&perm, &mv);
+                apply_gtsp_move(&mut perm, &mv);
+                assert!((fitness.eval(&perm) - before - delta).abs() < 1.0e-9);
+            }
+        }
+    }
+}
@@ -36,8 +36,23 @@ pub fn gtsp_basic_stats_gen_instance() {
     plot_gtsp_solution(&positions, &perm, 0.0, &colors, 4, "out/points.svg", "gen points").unwrap();
 }
 
-pub fn gtsp_gen_problem(vert_count: usize, group_count: usize, file_name: &str) {
-    let (problem, positions) = gen_euclidean_gtsp_problem(vert_count, group_count);
+pub fn gtsp_gen_problem(generator: GtspGenerator, vert_count: usize, group_count: usize, file_name: &str) {
+    let (problem, positions) = gen_gtsp_problem(generator, vert_count, group_count);
+    gtsp_save_gen_problem(problem, positions, file_name);
+}
+
+// GTSPLIB style name, e.g. 11berlin52
+pub fn gtsp_gen_problem_from_tsplib(tsplib_name: &str, group_count: usize) {
+    let tsplib = load_tsplib(&format!("data/tsp/{}.tsp", tsplib_name));
+    let (problem, positions) = gtsp_problem_from_tsplib(&tsplib, group_count);
+    gtsp_save_gen_problem(problem, positions, &format!("{}{}", group_count, tsplib_name));
+}
+
+fn gtsp_save_gen_problem(mut problem: GtspProblem, positions: Vec<GroupVertPos>, file_name: &str) {
+    // branch and bound doesn't finish on the bigger instances, best_known stays unverified
+    if problem.groups.len() <= EXACT_MAX_GROUPS && gtsp_held_karp_fits(&problem) {
+        problem.best_known = gtsp_held_karp(Rc::new(problem.clone())).fitness;
+    }
     println!("vert count: {}", problem.vert_count);
     println!("group count: {}", problem.groups.len());
     println!("euclidean:  {}", are_distances_euclidean(&problem.distances));
@@ -164,6 +179,7 @@ pub fn gtsp_basic_stats_default_params(num_repetitions: usize, population_size:
             iter += 1;
             progress_bar_clear();
         }
+        print_avg_stats_gap(input_file, &method_names, &avg_stats, num_repetitions, gtsp_lower_bound(&problem, LOWER_BOUND_MAX_ITERS));
         let log_opt_value = process_avg_stats(&mut avg_stats, opt_value, num_iters, num_repetitions);
         plot_multiple(&avg_stats, &method_names, &TAB_COLORS, format!("out/gtsp/default_{}.svg", input_file).as_str(), input_file, log_opt_value, "Log avg. fitness", false, true).unwrap();
     }
@@ -297,25 +313,24 @@ pub fn gtsp_local_search_stats(num_repetitions: usize) {
         let local_termination_cond = MaxIterTerminationCond { n_iters: num_iters };
         let local_selection = IdentitySelection {};
         let local_replacement_strategy = TruncationReplacementStrategy {};
-        let local_crossover = IdentityCrossover {};
 
-        let move_perturbation = CombinePerturbeMutOps { mut_ops: vec![
-            ProbPerturbeMutOp { prob: 0.5, op: Rc::from(GtspRandGroupVertPerturbation::new(problem.groups.len()))},
-            ProbPerturbeMutOp { prob: 0.5, op: Rc::from(GtspMoveGroupPerturbation {})}
+        let move_perturbation = CombineMovePerturbeMutOps { mut_ops: vec![
+            ProbMovePerturbeMutOp { prob: 0.5, op: Rc::from(GtspRandGroupVertPerturbation::new(problem.groups.len()))},
+            ProbMovePerturbeMutOp { prob: 0.5, op: Rc::from(GtspMoveGroupPerturbation {})}
         ]};
-        let swap_perturbation = CombinePerturbeMutOps { mut_ops: vec![
-            ProbPerturbeMutOp { prob: 0.5, op: Rc::from(GtspRandGroupVertPerturbation::new(problem.groups.len()))},
-            ProbPerturbeMutOp { prob: 0.5, op: Rc::from(GtspSwapGroupPerturbation {})}
+        let swap_perturbation = CombineMovePerturbeMutOps { mut_ops: vec![
+            ProbMovePerturbeMutOp { prob: 0.5, op: Rc::from(GtspRandGroupVertPerturbation::new(problem.groups.len()))},
+            ProbMovePerturbeMutOp { prob: 0.5, op: Rc::from(GtspSwapGroupPerturbation {})}
         ]};
-        let rev_perturbation = CombinePerturbeMutOps { mut_ops: vec![
-            ProbPerturbeMutOp { prob: 0.5, op: Rc::from(GtspRandGroupVertPerturbation::new(problem.groups.len()))},
-            ProbPerturbeMutOp { prob: 0.5, op: Rc::from(GtspReverseGroupPerturbation {})}
+        let rev_perturbation = CombineMovePerturbeMutOps { mut_ops: vec![
+            ProbMovePerturbeMutOp { prob: 0.5, op: Rc::from(GtspRandGroupVertPerturbation::new(problem.groups.len()))},
+            ProbMovePerturbeMutOp { prob: 0.5, op: Rc::from(GtspReverseGroupPerturbation {})}
         ]};
-        let opt_perturbation = CombinePerturbeMutOps { mut_ops: vec![
-            ProbPerturbeMutOp { prob: 0.9, op: Rc::from(GtspRandGroupVertPerturbation::new(problem.groups.len()))},
-            ProbPerturbeMutOp { prob: 0.0, op: Rc::from(GtspMoveGroupPerturbation {})},
-            ProbPerturbeMutOp { prob: 0.0, op: Rc::from(GtspSwapGroupPerturbation {})},
-            ProbPerturbeMutOp { prob: 0.9, op: Rc::from(GtspReverseGroupPerturbation {})}
+        let opt_perturbation = CombineMovePerturbeMutOps { mut_ops: vec![
+            ProbMovePerturbeMutOp { prob: 0.9, op: Rc::from(GtspRandGroupVertPerturbation::new(problem.groups.len()))},
+            ProbMovePerturbeMutOp { prob: 0.0, op: Rc::from(GtspMoveGroupPerturbation {})},
+            ProbMovePerturbeMutOp { prob: 0.0, op: Rc::from(GtspSwapGroupPerturbation {})},
+            ProbMovePerturbeMutOp { prob: 0.9, op: Rc::from(GtspReverseGroupPerturbation {})}
         ]};
 
         let mut avg_stats = vec![BSFSingleObjStatistics { fitness: vec![0.0f64; num_iters]}; method_names.len()];
@@ -323,48 +338,40 @@ pub fn gtsp_local_search_stats(num_repetitions: usize) {
         for _rep in 0..num_repetitions {
             progress_bar_text(&format!("progress: {:.2}%", 100.0 * (iter as f64) / (total_iters as f64)));
             let (_, stats1) : (BSFSingleObjSolution<GtspPermutation>, BSFSingleObjStatistics)
-                = evolutionary_search(
+                = evolutionary_search_delta(
                 &mut fitness, 
                 local_init_population.clone(),
                 &local_selection,
-                &local_crossover,
                 move_perturbation.clone(), 
                 &local_replacement_strategy,
-                &local_termination_cond,
-                false);
+                &local_termination_cond);
 
             let (_, stats2) : (BSFSingleObjSolution<GtspPermutation>, BSFSingleObjStatistics)
-                 = evolutionary_search(
+                 = evolutionary_search_delta(
                 &mut fitness, 
                 local_init_population.clone(),
                 &local_selection,
-                &local_crossover,
                 swap_perturbation.clone(), 
                 &local_replacement_strategy,
-                &local_termination_cond,
-                false);
+                &local_termination_cond);
 
             let (_, stats3) : (BSFSingleObjSolution<GtspPermutation>, BSFSingleObjStatistics)
-                 = evolutionary_search(
+                 = evolutionary_search_delta(
                 &mut fitness, 
                 local_init_population.clone(),
                 &local_selection,
-                &local_crossover,
                 rev_perturbation.clone(), 
                 &local_replacement_strategy,
-                &local_termination_cond,
-                false);
+                &local_termination_cond);
             
             let (_, stats4) : (BSFSingleObjSolution<GtspPermutation>, BSFSingleObjStatistics)
-                = evolutionary_search(
+                = evolutionary_search_delta(
                &mut fitness, 
                local_init_population.clone(),
                &local_selection,
-               &local_crossover,
                opt_perturbation.clone(), 
                &local_replacement_strategy,
-               &local_termination_cond,
-               false);
+               &local_termination_cond);
 
             let curr_stats = vec![stats1, stats2, stats3, stats4];
             for s in 0..avg_stats.len() {
@@ -375,6 +382,7 @@ pub fn gtsp_local_search_stats(num_repetitions: usize) {
             progress_bar_clear();
             iter += 1;
         }
+        print_avg_stats_gap(input_file, &method_names, &avg_stats, num_repetitions, gtsp_lower_bound(&problem, LOWER_BOUND_MAX_ITERS));
         let log_opt_value = process_avg_stats(&mut avg_stats, opt_value, num_iters, num_repetitions);
         plot_multiple(&avg_stats, &method_names, &TAB_COLORS, format!("out/gtsp/local_{}.svg", input_file).as_str(), input_file, log_opt_value, "Log avg. fitness", false, true).unwrap();
         
@@ -719,12 +727,61 @@ pub fn gtsp_evolutionary_search_stats(num_repetitions: usize, population_size: u
             iter += 1;
             progress_bar_clear();
         }
+        print_avg_stats_gap(input_file, &method_names, &avg_stats, num_repetitions, gtsp_lower_bound(&problem, LOWER_BOUND_MAX_ITERS));
         let log_opt_value = process_avg_stats(&mut avg_stats, opt_value, num_iters, num_repetitions);
         plot_multiple(&avg_stats, &method_names, &TAB_COLORS, format!("out/gtsp/evo_{}.svg", input_file).as_str(), input_file, log_opt_value, "Log avg. fitness", false, true).unwrap();
     }
     end_progress_bar();
 }
 
+// Plots the operator probabilities learned during a single run of every adaptive method
+pub fn gtsp_adaptive_operator_stats(num_iters: usize, population_size: usize) {
+    let method_names = ["probability_matching", "adaptive_pursuit", "ucb", "sliding_window_ucb"];
+    let methods = [
+        OperatorSelectionMethod::ProbabilityMatching { p_min: 0.05, alpha: 0.3 },
+        OperatorSelectionMethod::AdaptivePursuit { p_min: 0.05, alpha: 0.3, beta: 0.3 },
+        OperatorSelectionMethod::Ucb { c: 0.1 },
+        OperatorSelectionMethod::SlidingWindowUcb { c: 0.1, window: 5 * population_size }
+    ];
+    let mut_ops_names = vec!["rand vert", "move", "swap", "rev"];
+    let input_files = ["g1", "a", "b", "c", "d", "e", "f"];
+    for input_file in input_files {
+        let problem = Rc::from(load_gtsp_problem(format!("data/gtsp/{}.txt", input_file).as_str()));
+        let mut fitness = GtspFitness {};
+        let evo_init_population = InitRandomGtspPopulation { spec: problem.clone(), size: population_size };
+        let evo_termination_cond = MaxIterTerminationCond { n_iters: num_iters };
+        let evo_selection = RankSelection { select_count: population_size / 2 };
+        let evo_replacement_strategy = TruncationReplacementStrategy {};
+
+        for m in 0..methods.len() {
+            let perturbation = AdaptiveCombinePerturbeMutOps::<GtspPermutation>::new(vec![
+                Rc::from(GtspRandGroupVertPerturbation::new(problem.groups.len())),
+                Rc::from(GtspMoveGroupPerturbation {}),
+                Rc::from(GtspSwapGroupPerturbation {}),
+                Rc::from(GtspReverseGroupPerturbation {})
+            ], Rc::new(GtspFitness {}), methods[m]);
+            let mut_selector = perturbation.selector.clone();
+            let crossover = AdaptiveGtspGeneralCrossover::new(methods[m]);
+
+            let (_, _) : (BSFSingleObjSolution<GtspPermutation>, BSFSingleObjStatistics)
+                = evolutionary_search(
+                &mut fitness,
+                evo_init_population.clone(),
+                &evo_selection,
+                &crossover,
+                perturbation,
+                &evo_replacement_strategy,
+                &evo_termination_cond,
+                false);
+
+            let mut_stats = mut_selector.borrow().probs_statistics();
+            plot_multiple(&mut_stats, &mut_ops_names, &TAB_COLORS, format!("out/gtsp/adaptive_mut_{}_{}.svg", method_names[m], input_file).as_str(), input_file, 0.0, "Probability", false, true).unwrap();
+            let cross_stats = crossover.selector.borrow().probs_statistics();
+            plot_multiple(&cross_stats, &AdaptiveGtspGeneralCrossover::OPS_NAMES.to_vec(), &TAB_COLORS, format!("out/gtsp/adaptive_cross_{}_{}.svg", method_names[m], input_file).as_str(), input_file, 0.0, "Probability", false, true).unwrap();
+        }
+    }
+}
+
 pub fn gtsp_stats_optimized_params(num_repetitions: usize, population_size: usize) {
     let method_names = vec!["local", "local heuristic", "evo", "evo heuristic"];
     let is_method_local = [true, true, false, false];
@@ -741,8 +798,8 @@ pub fn gtsp_stats_optimized_params(num_repetitions: usize, population_size: usiz
         let mut fitness = GtspFitness {};
         let opt_value = problem.best_known;
 
-        let local_heuristic_init_population = InitHeuristicGtspPopulation { spec: problem.clone(), size: 1 };
-        let evo_heuristic_init_population = InitHeuristicGtspPopulation { spec: problem.clone(), size: population_size };
+        let local_heuristic_init_population = InitHeuristicGtspPopulation { spec: problem.clone(), size: 1, construction: GtspConstruction::NearestNeighbour, heuristic_fraction: 0.25, candidates: None };
+        let evo_heuristic_init_population = InitHeuristicGtspPopulation { spec: problem.clone(), size: population_size, construction: GtspConstruction::NearestNeighbour, heuristic_fraction: 0.25, candidates: None };
 
         let local_init_population = InitRandomGtspPopulation { spec: problem.clone(), size: 1 };
         let local_termination_cond = MaxIterTerminationCond { n_iters: num_iters * population_size };
@@ -831,6 +888,7 @@ pub fn gtsp_stats_optimized_params(num_repetitions: usize, population_size: usiz
             iter += 1;
             progress_bar_clear();
         }
+        print_avg_stats_gap(input_file, &method_names, &avg_stats, num_repetitions, gtsp_lower_bound(&problem, LOWER_BOUND_MAX_ITERS));
         let log_opt_value = process_avg_stats(&mut avg_stats, opt_value, num_iters, num_repetitions);
         plot_multiple(&avg_stats, &method_names, &TAB_COLORS, format!("out/gtsp/best_{}.svg", input_file).as_str(), input_file, log_opt_value, "Log avg. fitness", false, true).unwrap();
     }
@@ -864,7 +922,7 @@ pub fn viz_all() {
         let positions = load_gtsp_positions(format!("data/gtsp/{}_pos.txt", input_file).as_str());
         let mut fitness = GtspFitness {};
 
-        let heuristic_init_population = InitHeuristicGtspPopulation { spec: problem.clone(), size: 1 };
+        let heuristic_init_population = InitHeuristicGtspPopulation { spec: problem.clone(), size: 1, construction: GtspConstruction::NearestNeighbour, heuristic_fraction: 0.25, candidates: None };
         let heuristic_sol = InitFunc::init(&heuristic_init_population);
         let heuristic_fitness = fitness.eval(&heuristic_sol);
         
@@ -893,7 +951,7 @@ pub fn viz_all() {
                 &local_termination_cond,
                 false);
 
-        let evo_heuristic_init_population = InitHeuristicGtspPopulation { spec: problem.clone(), size: population_size };
+        let evo_heuristic_init_population = InitHeuristicGtspPopulation { spec: problem.clone(), size: population_size, construction: GtspConstruction::NearestNeighbour, heuristic_fraction: 0.25, candidates: None };
         let evo_init_population = InitRandomGtspPopulation { spec: problem.clone(), size: population_size };
         let best_termination_cond = MaxIterTerminationCond { n_iters: num_iters };
         let evo_termination_cond = MaxIterTerminationCond { n_iters: evo_max_iters[i] };
@@ -0,0 +1,141 @@
+use std::rc::Rc;
+
+use crate::*;
+
+// Noon-Bean transformation of GTSP to asymmetric TSP. Vertices of every group form a zero length
+// cycle, a tour entering the group in the vertex v goes around the cycle and leaves the group
+// from the predecessor of v. The arc leaving the group from u costs big_m plus the GTSP distance
+// from the successor of u, other arcs inside the groups cost 2 big_m. big_m is larger than any
+// GTSP tour, so optimal ATSP tours visit the groups in one go and have the length of the GTSP
+// tour plus groups count times big_m.
+#[derive(Clone)]
+pub struct NoonBeanAtsp {
+    pub spec: Rc<GtspProblem>,
+    pub distances: Distances,
+    pub big_m: f64,
+    // group and local index of every vertex
+    vert_group: Vec<GroupVert>
+}
+
+impl NoonBeanAtsp {
+    pub fn new(spec: Rc<GtspProblem>) -> Self {
+        let n = spec.vert_count;
+        let mut vert_group = vec![GroupVert { group: 0, vert: 0 }; n];
+        for (g, group) in spec.groups.iter().enumerate() {
+            for (i, v) in group.iter().enumerate() {
+                vert_group[*v] = GroupVert { group: g, vert: i };
+            }
+        }
+        let mut max_dist = 0.0f64;
+        for u in 0..n {
+            for v in 0..n {
+                max_dist = max_dist.max(spec.distances.get(u, v));
+            }
+        }
+        let big_m = spec.groups.len() as f64 * max_dist + 1.0;
+        let mut transformed = NoonBeanAtsp { spec, distances: Distances::new(n, false), big_m, vert_group };
+        for u in 0..n {
+            let succ = transformed.group_succ(u);
+            for v in 0..n {
+                let dist = if u == v {
+                    0.0
+                } else if transformed.vert_group[u].group != transformed.vert_group[v].group {
+                    big_m + transformed.spec.distances.get(succ, v)
+                } else if v == succ {
+                    0.0
+                } else {
+                    2.0 * big_m
+                };
+                transformed.distances.set(u, v, dist);
+            }
+        }
+        transformed
+    }
+
+    // successor of the vertex in the zero cycle of its group
+    fn group_succ(&self, vert: usize) -> usize {
+        let gv = self.vert_group[vert];
+        let group = &self.spec.groups[gv.group];
+        group[(gv.vert + 1) % group.len()]
+    }
+
+    // length of the ATSP tour corresponding to the GTSP tour of the given length, a single group
+    // has no arcs leaving it
+    pub fn atsp_len(&self, gtsp_len: f64) -> f64 {
+        if self.spec.groups.len() == 1 {
+            return gtsp_len;
+        }
+        gtsp_len + self.spec.groups.len() as f64 * self.big_m
+    }
+
+    pub fn encode(&self, perm: &GtspPermutation) -> TspPermutation {
+        let mut vert_perm = Vec::<usize>::with_capacity(self.spec.vert_count);
+        for gv in &perm.perm {
+            let group = &self.spec.groups[gv.group];
+            for i in 0..group.len() {
+                vert_perm.push(group[(gv.vert + i) % group.len()]);
+            }
+        }
+        TspPermutation { vert_perm }
+    }
+
+    // Every arc leaving a group gives the group with the successor of the arc start. None if the
+    // decoded tour length doesn't match the ATSP tour length, which happens when the tour doesn't
+    // go around the zero cycles.
+    pub fn decode(&self, tour: &TspPermutation) -> Option<GtspPermutation> {
+        let n = tour.vert_perm.len();
+        let mut perm = Vec::<GroupVert>::with_capacity(self.spec.groups.len());
+        for i in 0..n {
+            let u = tour.vert_perm[i];
+            let v = tour.vert_perm[(i + 1) % n];
+            if self.vert_group[u].group != self.vert_group[v].group || (self.spec.groups.len() == 1 && i == n - 1) {
+                perm.push(self.vert_group[self.group_succ(u)]);
+            }
+        }
+        let decoded = GtspPermutation { spec: self.spec.clone(), perm };
+        let atsp_len = tour_len(&tour.vert_perm, &|u: &usize, v: &usize| self.distances.get(*u, *v));
+        let gtsp_len = GtspFitness {}.eval(&decoded);
+        let tolerance = 1e-9 * atsp_len.abs().max(1.0);
+        if decoded.perm.len() != self.spec.groups.len() || (self.atsp_len(gtsp_len) - atsp_len).abs() > tolerance {
+            return None;
+        }
+        Some(decoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_noon_bean() {
+        let (problem, _) = gen_euclidean_gtsp_problem(12, 4);
+        let problem = Rc::new(problem);
+        let transformed = NoonBeanAtsp::new(problem.clone());
+        let gtsp_fitness = GtspFitness {};
+        let atsp_fitness = TspFitness { distances: transformed.distances.clone() };
+        let perm: GtspPermutation = InitFunc::init(&InitRandomGtspPopulation { spec: problem.clone(), size: 1 });
+        let tour = transformed.encode(&perm);
+        let gtsp_len = gtsp_fitness.eval(&perm);
+        assert!((atsp_fitness.eval(&tour) - transformed.atsp_len(gtsp_len)).abs() < 1e-6);
+        let decoded = transformed.decode(&tour).unwrap();
+        assert!((gtsp_fitness.eval(&decoded) - gtsp_len).abs() < 1e-9);
+        // optimal ATSP tour is the optimal GTSP tour
+        let atsp_opt = tsp_held_karp(&transformed.distances);
+        let gtsp_opt = gtsp_held_karp(problem.clone());
+        let decoded = transformed.decode(&atsp_opt.value).unwrap();
+        assert!((gtsp_fitness.eval(&decoded) - gtsp_opt.fitness).abs() < 1e-6);
+        // tours leaving the zero cycles are rejected
+        let mut broken = tour.clone();
+        broken.vert_perm.swap(0, 1);
+        assert!(transformed.decode(&broken).is_none() || problem.groups[perm.perm[0].group].len() < 3);
+
+        let (problem, _) = gen_euclidean_gtsp_problem(3, 1);
+        let problem = Rc::new(problem);
+        let transformed = NoonBeanAtsp::new(problem.clone());
+        let perm: GtspPermutation = InitFunc::init(&InitRandomGtspPopulation { spec: problem.clone(), size: 1 });
+        let decoded = transformed.decode(&transformed.encode(&perm)).unwrap();
+        assert_eq!(decoded.perm[0].vert, perm.perm[0].vert);
+    }
+}
@@ -1,10 +1,12 @@
 use crate::*;
 
+use std::rc::Rc;
 use std::fs::File;
 use std::io::BufReader;
 use std::io::BufRead;
 use std::io::Write;
 use rand::Rng;
+use rand_distr::{Normal, Distribution};
 
 pub fn load_gtsp_problem(file_path: &str) -> GtspProblem {
     let file = File::open(file_path).expect("file wasn't found.");
@@ -17,7 +19,7 @@ pub fn load_gtsp_problem(file_path: &str) -> GtspProblem {
         vert_count,
         best_known,
         groups: vec![Vec::<usize>::new(); group_count],
-        distances: DistanceHalfMatrix::new(vert_count)
+        distances: Distances::new(vert_count, true)
     };
 
     for g in 0..group_count {
@@ -29,15 +31,18 @@ pub fn load_gtsp_problem(file_path: &str) -> GtspProblem {
         problem.groups[g] = line_nums;
     }
 
+    // asymmetric distances are kept in the full matrix
+    let mut distances = DistanceFullMatrix::new(vert_count);
     for v in 0..vert_count {
         let line = &lines[v + 3 + group_count];
         let line_nums : Vec<f64> = line.split_whitespace()
                 .map(|num_str| num_str.parse::<f64>().unwrap())
                 .collect();
         for u in 0..vert_count {
-            problem.distances.set(v, u, line_nums[u]);
+            distances.set(v, u, line_nums[u]);
         }
     }
+    problem.distances = Distances::from_full_matrix(distances);
 
     problem
 }
@@ -65,6 +70,37 @@ pub fn save_gtsp_problem(file_path: &str, problem: &GtspProblem)
     }
 }
 
+// Saves the solution in the format described in data/gtsp/format.txt
+pub fn save_gtsp_solution(file_path: &str, solution: &BSFSingleObjSolution<GtspPermutation>)
+{
+    let mut file = File::create(file_path).expect("unable to create a file.");
+    let perm = &solution.value.perm;
+    let groups: Vec<String> = perm.iter().map(|gvert| (gvert.group + 1).to_string()).collect();
+    let verts: Vec<String> = perm.iter().map(|gvert| (solution.value.spec.groups[gvert.group][gvert.vert] + 1).to_string()).collect();
+    file.write_all(format!("{}\n{}\n{}\n{}\n", solution.fitness, perm.len(), groups.join(" "), verts.join(" ")).as_bytes()).unwrap();
+}
+
+pub fn load_gtsp_solution(file_path: &str, spec: Rc<GtspProblem>) -> BSFSingleObjSolution<GtspPermutation>
+{
+    let file = File::open(file_path).expect("file wasn't found.");
+    let reader = BufReader::new(file);
+    let lines: Vec<String> = reader.lines().map(|line| line.unwrap()).collect();
+    let fitness = lines[0].parse::<f64>().unwrap();
+    let group_count = lines[1].parse::<usize>().unwrap();
+    let groups: Vec<usize> = lines[2].split_whitespace().map(|num_str| num_str.parse::<usize>().unwrap() - 1).collect();
+    let verts: Vec<usize> = lines[3].split_whitespace().map(|num_str| num_str.parse::<usize>().unwrap() - 1).collect();
+    if groups.len() != group_count || verts.len() != group_count {
+        panic!("incorrect number of groups in the solution");
+    }
+    let perm = (0..group_count)
+        .map(|i| GroupVert {
+            group: groups[i],
+            vert: spec.groups[groups[i]].iter().position(|v| *v == verts[i]).expect("vertex is not in its group")
+        })
+        .collect();
+    BSFSingleObjSolution { value: GtspPermutation { spec, perm }, fitness }
+}
+
 pub fn load_gtsp_positions(file_path: &str) -> Vec<GroupVertPos>
 {
     let file = File::open(file_path).expect("file wasn't found.");
@@ -96,7 +132,7 @@ pub fn save_gtsp_positions(file_path: &str, positions: &Vec<GroupVertPos>)
     }
 }
 
-pub fn are_distances_euclidean(distances: &DistanceHalfMatrix) -> bool {
+pub fn are_distances_euclidean<DistanceMatrixT: DistanceMatrix>(distances: &DistanceMatrixT) -> bool {
     let vert_count = distances.get_vert_count();
     let mut all_euclidean = true;
     for v1 in 0..vert_count {
@@ -116,7 +152,7 @@ pub fn are_distances_euclidean(distances: &DistanceHalfMatrix) -> bool {
     all_euclidean
 }
 
-pub fn are_distances_a_metric(distances: &DistanceHalfMatrix) -> bool {
+pub fn are_distances_a_metric<DistanceMatrixT: DistanceMatrix>(distances: &DistanceMatrixT) -> bool {
     let vert_count = distances.get_vert_count();
     let mut is_metric = true;
     for v1 in 0..vert_count {
@@ -126,7 +162,7 @@ pub fn are_distances_a_metric(distances: &DistanceHalfMatrix) -> bool {
         }
         for v2 in 0..vert_count {
             let c = distances.get(v1, v2);
-            if c < 0.0 {
+            if c < 0.0 || c != distances.get(v2, v1) {
                 is_metric = false;
                 break;
             }
@@ -184,9 +220,10 @@ pub fn gen_euclidean_gtsp_problem(vert_count: usize, group_count: usize) -> (Gts
     assert!(group_count < vert_count);
     let mut problem = GtspProblem {
         vert_count,
+        // unknown, see gtsp_exact
         best_known: 1.0,
         groups: vec![Vec::<usize>::new(); group_count],
-        distances: DistanceHalfMatrix::new(vert_count)
+        distances: Distances::new(vert_count, true)
     };
     // generate vertex positons
     let mut positions = vec![GroupVertPos { group: 0, pos: [0.0, 0.0]}; vert_count];
@@ -197,7 +234,7 @@ pub fn gen_euclidean_gtsp_problem(vert_count: usize, group_count: usize) -> (Gts
         }
     }
     // compute distances
-    problem.distances = gtsp_positions_to_distances(&positions);
+    problem.distances = gtsp_positions_to_distances(&positions).into();
     // k-means clustering
     let mut etalons = vec![[0.0; 2]; group_count];
     // init etalons to some vertices
@@ -274,14 +311,131 @@ pub fn gen_euclidean_gtsp_problem(vert_count: usize, group_count: usize) -> (Gts
     (problem, positions)
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GtspGenerator {
+    // uniform points clustered by k-means, see gen_euclidean_gtsp_problem
+    KMeans,
+    // groups are gaussian blobs around uniform centers
+    GaussianBlobs { std_dev: f64 },
+    // groups are cells of a grid over the uniform points
+    Grid,
+    // uniform points with uniformly random groups
+    RandomGroups
+}
+
+// euclidean instance with uniform points in a square with the side sqrt(vert_count)
+pub fn gen_gtsp_problem(generator: GtspGenerator, vert_count: usize, group_count: usize) -> (GtspProblem, Vec<GroupVertPos>)
+{
+    assert!(group_count <= vert_count);
+    let dim_size = (vert_count as f64).sqrt().ceil();
+    let mut positions = Vec::<[f64; 2]>::with_capacity(vert_count);
+    // the first group_count vertices go to every group once so that no group is empty
+    let vert_groups: Vec<usize> = (0..vert_count).map(|v| if v < group_count { v } else { rand::thread_rng().gen_range(0..group_count) }).collect();
+    match generator {
+        GtspGenerator::KMeans => return gen_euclidean_gtsp_problem(vert_count, group_count),
+        GtspGenerator::GaussianBlobs { std_dev } => {
+            let centers: Vec<[f64; 2]> = (0..group_count).map(|_| [0, 1].map(|_| rand::random::<f64>() * dim_size)).collect();
+            let normal = Normal::new(0.0, std_dev).unwrap();
+            for v in 0..vert_count {
+                positions.push(centers[vert_groups[v]].map(|c| c + normal.sample(&mut rand::thread_rng())));
+            }
+        },
+        GtspGenerator::Grid => {
+            let cols = (group_count as f64).sqrt().ceil() as usize;
+            let rows = group_count.div_ceil(cols);
+            let cell_size = [dim_size / cols as f64, dim_size / rows as f64];
+            for v in 0..vert_count {
+                let cell = [vert_groups[v] % cols, vert_groups[v] / cols];
+                positions.push([0, 1].map(|d| (cell[d] as f64 + rand::random::<f64>()) * cell_size[d]));
+            }
+        },
+        GtspGenerator::RandomGroups => {
+            for _ in 0..vert_count {
+                positions.push([0, 1].map(|_| rand::random::<f64>() * dim_size));
+            }
+        }
+    }
+    let distances = vert_positions_to_distances(&positions).into();
+    gtsp_problem_from_vert_groups(positions, &vert_groups, group_count, distances)
+}
+
+fn gtsp_problem_from_vert_groups(positions: Vec<[f64; 2]>, vert_groups: &[usize], group_count: usize, distances: Distances) -> (GtspProblem, Vec<GroupVertPos>)
+{
+    let mut groups = vec![Vec::<usize>::new(); group_count];
+    for v in 0..vert_groups.len() {
+        groups[vert_groups[v]].push(v);
+    }
+    let problem = GtspProblem {
+        vert_count: vert_groups.len(),
+        // unknown, see gtsp_exact
+        best_known: 1.0,
+        groups,
+        distances
+    };
+    let positions = positions.into_iter().zip(vert_groups).map(|(pos, group)| GroupVertPos { group: *group, pos }).collect();
+    (problem, positions)
+}
+
+// Fischetti, Salazar-Gonzalez and Toth clustering used by GTSPLIB: group_count centers as far
+// as possible from each other, starting with the farthest pair of vertices and adding the vertex
+// farthest from the chosen centers, every vertex belongs to its nearest center. Distances keep
+// the TSPLIB rounding. Reproduces the GTSPLIB optima of 11berlin52 and 16eil76, but not 10att48.
+pub fn gtsp_problem_from_tsplib(tsplib: &TsplibProblem, group_count: usize) -> (GtspProblem, Vec<GroupVertPos>)
+{
+    if tsplib.positions.len() != tsplib.dimension {
+        panic!("the TSPLIB instance has no coordinates");
+    }
+    assert!(group_count >= 2 && group_count <= tsplib.dimension);
+    let distances = tsplib.distances();
+    let n = tsplib.dimension;
+    let mut farthest_pair = (0, 1);
+    for u in 0..n {
+        for v in (u + 1)..n {
+            if distances.get(u, v) > distances.get(farthest_pair.0, farthest_pair.1) {
+                farthest_pair = (u, v);
+            }
+        }
+    }
+    let mut centers = vec![farthest_pair.0, farthest_pair.1];
+    let mut center_dist: Vec<f64> = (0..n).map(|v| distances.get(farthest_pair.0, v).min(distances.get(farthest_pair.1, v))).collect();
+    while centers.len() < group_count {
+        let farthest = (0..n).filter(|v| !centers.contains(v))
+            .max_by(|u, v| center_dist[*u].total_cmp(&center_dist[*v]).then(v.cmp(u)))
+            .unwrap();
+        centers.push(farthest);
+        for v in 0..n {
+            center_dist[v] = center_dist[v].min(distances.get(farthest, v));
+        }
+    }
+    let vert_groups: Vec<usize> = (0..n).map(|v| {
+        match centers.iter().position(|c| *c == v) {
+            Some(g) => g,
+            None => (0..group_count).min_by(|g, h| distances.get(centers[*g], v).total_cmp(&distances.get(centers[*h], v))).unwrap()
+        }
+    }).collect();
+    gtsp_problem_from_vert_groups(tsplib.positions.clone(), &vert_groups, group_count, distances)
+}
+
 pub fn gtsp_positions_to_distances(positions: &Vec<GroupVertPos>) -> DistanceHalfMatrix {
     vert_positions_to_distances(&positions.iter().map(|gpos| gpos.pos).collect())
 }
 
-pub fn gtsp_group_avg_distances(problem: GtspProblem) -> DistanceHalfMatrix {
-    let mut distances = DistanceHalfMatrix::new(problem.groups.len());
+// euclidean distances computed on demand, for instances too large for a matrix
+pub fn gtsp_positions_to_coord_distances(positions: &Vec<GroupVertPos>, knn_count: Option<usize>) -> CoordDistances {
+    let vert_positions = positions.iter().map(|gpos| gpos.pos).collect();
+    match knn_count {
+        Some(k) => CoordDistances::with_knn_cache(vert_positions, TsplibEdgeWeightType::Euclidean, k),
+        None => CoordDistances::new(vert_positions, TsplibEdgeWeightType::Euclidean)
+    }
+}
+
+pub fn gtsp_group_avg_distances(problem: GtspProblem) -> Distances {
+    let mut distances = Distances::new(problem.groups.len(), problem.distances.is_symmetric());
     for g1 in 0..problem.groups.len() {
-        for g2 in 0..g1 {
+        for g2 in 0..problem.groups.len() {
+            if g1 == g2 || (g2 > g1 && problem.distances.is_symmetric()) {
+                continue;
+            }
             let mut dist_sum = 0.0;
             for v1 in &problem.groups[g1] {
                 for v2 in &problem.groups[g2] {
@@ -363,3 +517,48 @@ pub fn jarvis_convex_hull(points: &Vec<GroupVertPos>, out_hull: &mut Vec<GroupVe
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_generators() {
+        for generator in [GtspGenerator::KMeans, GtspGenerator::GaussianBlobs { std_dev: 0.5 }, GtspGenerator::Grid, GtspGenerator::RandomGroups] {
+            let (problem, positions) = gen_gtsp_problem(generator, 60, 7);
+            let mut verts: Vec<usize> = problem.groups.iter().flatten().copied().collect();
+            verts.sort();
+            assert_eq!(verts, (0..60).collect::<Vec<usize>>());
+            assert!(problem.groups.iter().all(|group| !group.is_empty()));
+            for (g, group) in problem.groups.iter().enumerate() {
+                assert!(group.iter().all(|v| positions[*v].group == g));
+            }
+        }
+        // GTSPLIB optimum of 11berlin52
+        let (problem, _) = gtsp_problem_from_tsplib(&load_tsplib("data/tsp/berlin52.tsp"), 11);
+        assert_eq!(gtsp_exact(Rc::new(problem)).fitness, 4040.0);
+    }
+
+    #[test]
+    fn test_saved_solution_roundtrip() {
+        let file_path = std::env::temp_dir().join("eoa_test_gtsp.txt");
+        let (problem, _) = gen_euclidean_gtsp_problem(12, 4);
+        let problem = Rc::new(problem);
+        let fitness = GtspFitness {};
+        let value: GtspPermutation = InitFunc::init(&InitRandomGtspPopulation { spec: problem.clone(), size: 1 });
+        let solution = BSFSingleObjSolution { fitness: fitness.eval(&value), value };
+        save_gtsp_solution(file_path.to_str().unwrap(), &solution);
+        // groups and vertices are numbered from 1
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        let groups: Vec<usize> = solution.value.perm.iter().map(|gv| gv.group + 1).collect();
+        let verts: Vec<usize> = solution.value.perm.iter().map(|gv| problem.groups[gv.group][gv.vert] + 1).collect();
+        assert_eq!(lines[1], "4");
+        assert_eq!(lines[2], groups.iter().map(|g| g.to_string()).collect::<Vec<String>>().join(" "));
+        assert_eq!(lines[3], verts.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(" "));
+        let loaded = load_gtsp_solution(file_path.to_str().unwrap(), problem.clone());
+        assert_eq!(loaded.fitness, solution.fitness);
+        assert_eq!(fitness.eval(&loaded.value), solution.fitness);
+    }
+}
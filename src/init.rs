@@ -1,3 +1,4 @@
+use rand::Rng;
 use rand_distr::{Normal, Distribution};
 
 use crate::*;
@@ -35,7 +36,8 @@ pub struct InitRandomFloatVecPopulation {
     pub vec_size: usize,
     pub mean: f64,
     pub std_dev: f64,
-    pub bounds: Vec<Bounds>
+    pub bounds: Vec<Bounds>,
+    pub bound_handling: BoundHandling
 }
 
 impl InitRandomFloatVecPopulation {
@@ -45,7 +47,7 @@ impl InitRandomFloatVecPopulation {
         for i in 0..self.vec_size {
             let mut value = normal.sample(&mut rand::thread_rng());
             if self.bounds.len() == self.vec_size {
-                value = value.clamp(self.bounds[i].lower, self.bounds[i].upper);
+                value = handle_bound(value, self.mean, &self.bounds[i], self.bound_handling);
             }
             data.push(value);
         }
@@ -69,6 +71,35 @@ impl InitPopulation<FloatVec> for InitRandomFloatVecPopulation {
     }
 }
 
+// Uniformly random values inside the integer bounds
+#[derive(Clone)]
+pub struct InitRandomIntVecPopulation {
+    pub size: usize,
+    pub bounds: Vec<IntBounds>
+}
+
+impl InitRandomIntVecPopulation {
+    fn rand_data(&self) -> Vec<i64> {
+        self.bounds.iter().map(|b| rand::thread_rng().gen_range(b.lower..=b.upper)).collect()
+    }
+}
+
+impl InitFunc<IntVec> for InitRandomIntVecPopulation {
+    fn init(&self) -> IntVec {
+        IntVec { values: self.rand_data() }
+    }
+}
+
+impl InitPopulation<IntVec> for InitRandomIntVecPopulation {
+    fn init(&self) -> Vec<IntVec> {
+        let mut population = Vec::<IntVec>::with_capacity(self.size);
+        for _ in 0..self.size {
+            population.push(IntVec { values: self.rand_data() });
+        }
+        population
+    }
+}
+
 #[derive(Clone)]
 pub struct InitRandomNaiveBitVecPopulation {
     pub size: usize,
@@ -99,4 +130,26 @@ impl InitPopulation<NaiveBitVec> for InitRandomNaiveBitVecPopulation {
         }
         population
     }
-}
\ No newline at end of file
+}
+
+#[derive(Clone)]
+pub struct InitRandomBitVecPopulation {
+    pub size: usize,
+    pub bits_count: usize
+}
+
+impl InitFunc<BitVec> for InitRandomBitVecPopulation {
+    fn init(&self) -> BitVec {
+        BitVec::random(self.bits_count)
+    }
+}
+
+impl InitPopulation<BitVec> for InitRandomBitVecPopulation {
+    fn init(&self) -> Vec<BitVec> {
+        let mut population = Vec::<BitVec>::with_capacity(self.size);
+        for _ in 0..self.size {
+            population.push(BitVec::random(self.bits_count));
+        }
+        population
+    }
+}
@@ -1,19 +1,34 @@
 mod bins;
 mod bit_array;
+mod bound_handling;
+mod candidates;
 mod constrained;
+mod construction;
+mod coord_distances;
 mod crossover;
+mod direct_search;
+mod es;
+mod exact;
 mod g_funcs;
 mod g_func_stats;
+mod gp;
 mod gtsp_stats;
+mod gtsp_transform;
 mod gtsp_utils;
 mod gtsp;
 mod funcs;
 mod init;
+mod lower_bound;
+mod mixed;
+mod operator_selection;
 mod opt_data;
 mod opt_traits;
 mod opt_search;
 mod perturbe;
 mod plot;
+mod pso;
+mod pso_stats;
+mod random_key;
 mod multi_obj;
 mod replacement;
 mod selection;
@@ -25,20 +40,35 @@ mod utils;
 
 pub use bins::*;
 pub use bit_array::*;
+pub use bound_handling::*;
+pub use candidates::*;
 pub use constrained::*;
+pub use construction::*;
+pub use coord_distances::*;
 pub use crossover::*;
+pub use direct_search::*;
+pub use es::*;
+pub use exact::*;
 pub use g_funcs::*;
 pub use g_func_stats::*;
+pub use gp::*;
 pub use gtsp_stats::*;
+pub use gtsp_transform::*;
 pub use gtsp_utils::*;
 pub use gtsp::*;
 pub use funcs::*;
 pub use init::*;
+pub use lower_bound::*;
+pub use mixed::*;
+pub use operator_selection::*;
 pub use opt_data::*;
 pub use opt_traits::*;
 pub use opt_search::*;
 pub use perturbe::*;
 pub use plot::*;
+pub use pso::*;
+pub use pso_stats::*;
+pub use random_key::*;
 pub use multi_obj::*;
 pub use replacement::*;
 pub use selection::*;
@@ -0,0 +1,150 @@
+use crate::*;
+
+// Held-Karp (1-tree subgradient) lower bounds for TSP and GTSP, asymmetric distances are
+// symmetrized by taking the shorter direction which keeps the bound valid.
+
+// subgradient iterations used by the statistics
+pub const LOWER_BOUND_MAX_ITERS: usize = 1000;
+
+const NO_PARENT: usize = usize::MAX;
+
+// minimum spanning tree of the vertices 1..n plus the two cheapest edges of the vertex 0,
+// returns the length with the penalized weights and fills the vertex degrees
+fn one_tree<DistFunT: Fn(usize, usize) -> f64>(n: usize, dist: &DistFunT, penalties: &[f64], degrees: &mut [usize]) -> f64 {
+    let weight = |u: usize, v: usize| dist(u, v) + penalties[u] + penalties[v];
+    degrees.fill(0);
+    let mut in_tree = vec![false; n];
+    let mut key = vec![f64::INFINITY; n];
+    let mut parent = vec![NO_PARENT; n];
+    let mut total = 0.0;
+    key[1] = 0.0;
+    for _ in 1..n {
+        let v = (1..n).filter(|v| !in_tree[*v]).min_by(|u, v| key[*u].total_cmp(&key[*v])).unwrap();
+        in_tree[v] = true;
+        if parent[v] != NO_PARENT {
+            total += key[v];
+            degrees[v] += 1;
+            degrees[parent[v]] += 1;
+        }
+        for u in 1..n {
+            let w = weight(v, u);
+            if !in_tree[u] && w < key[u] {
+                key[u] = w;
+                parent[u] = v;
+            }
+        }
+    }
+    let mut root_edges: Vec<(f64, usize)> = (1..n).map(|u| (weight(0, u), u)).collect();
+    root_edges.sort_by(|a, b| a.0.total_cmp(&b.0));
+    for (w, u) in &root_edges[..2] {
+        total += w;
+        degrees[0] += 1;
+        degrees[*u] += 1;
+    }
+    total
+}
+
+fn nearest_neighbour_len<DistFunT: Fn(usize, usize) -> f64>(n: usize, dist: &DistFunT) -> f64 {
+    let mut visited = vec![false; n];
+    visited[0] = true;
+    let mut curr = 0;
+    let mut len = 0.0;
+    for _ in 1..n {
+        let next = (0..n).filter(|v| !visited[*v]).min_by(|u, v| dist(curr, *u).total_cmp(&dist(curr, *v))).unwrap();
+        len += dist(curr, next);
+        visited[next] = true;
+        curr = next;
+    }
+    len + dist(curr, 0)
+}
+
+// Subgradient optimization of the vertex penalties with the Polyak step towards the nearest
+// neighbour tour length, the step factor is halved when the bound stops improving
+fn held_karp_bound<DistFunT: Fn(usize, usize) -> f64>(n: usize, dist: &DistFunT, max_iters: usize) -> f64 {
+    if n < 3 {
+        return if n == 2 { 2.0 * dist(0, 1) } else { 0.0 };
+    }
+    let upper = nearest_neighbour_len(n, dist);
+    let mut penalties = vec![0.0; n];
+    let mut degrees = vec![0usize; n];
+    let mut best = f64::NEG_INFINITY;
+    let mut step_factor = 2.0;
+    let mut no_improvement = 0;
+    for _ in 0..max_iters {
+        let bound = one_tree(n, dist, &penalties, &mut degrees) - 2.0 * penalties.iter().sum::<f64>();
+        if bound > best {
+            best = bound;
+            no_improvement = 0;
+        } else {
+            no_improvement += 1;
+            if no_improvement >= 10 {
+                step_factor /= 2.0;
+                no_improvement = 0;
+            }
+        }
+        let norm: f64 = degrees.iter().map(|d| (*d as f64 - 2.0).powi(2)).sum();
+        // the 1-tree is a tour or the bound reached the tour length
+        if norm == 0.0 || bound >= upper || step_factor < 1e-6 {
+            break;
+        }
+        let step = step_factor * (upper - bound) / norm;
+        for v in 0..n {
+            penalties[v] += step * (degrees[v] as f64 - 2.0);
+        }
+    }
+    best
+}
+
+pub fn tsp_lower_bound<DistanceMatrixT: DistanceMatrix>(distances: &DistanceMatrixT, max_iters: usize) -> f64 {
+    held_karp_bound(distances.get_vert_count(), &|u, v| distances.get(u, v).min(distances.get(v, u)), max_iters)
+}
+
+// Held-Karp bound of the groups contracted to single vertices, the distance of two groups
+// is the shortest distance between their vertices
+pub fn gtsp_lower_bound(problem: &GtspProblem, max_iters: usize) -> f64 {
+    let group_count = problem.groups.len();
+    let mut group_distances = vec![f64::INFINITY; group_count * group_count];
+    for g in 0..group_count {
+        for h in (g + 1)..group_count {
+            let mut min = f64::INFINITY;
+            for u in &problem.groups[g] {
+                for v in &problem.groups[h] {
+                    min = min.min(problem.distances.get(*u, *v)).min(problem.distances.get(*v, *u));
+                }
+            }
+            group_distances[g * group_count + h] = min;
+            group_distances[h * group_count + g] = min;
+        }
+    }
+    held_karp_bound(group_count, &|g, h| group_distances[g * group_count + h], max_iters)
+}
+
+// relative distance of the solution from the lower bound in percents
+pub fn optimality_gap(found: f64, bound: f64) -> f64 {
+    100.0 * (found - bound) / bound.abs()
+}
+
+pub fn gap_summary(found: f64, bound: f64) -> String {
+    format!("found {}, bound {}, gap {:.2}%", found, bound, optimality_gap(found, bound))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn test_lower_bounds() {
+        let distances = load_tsplib("data/tsp/berlin52.tsp").distances();
+        let bound = tsp_lower_bound(&distances, 1000);
+        assert!(bound <= 7542.0 && bound >= 0.95 * 7542.0, "{}", bound);
+
+        let (problem, _) = gen_euclidean_gtsp_problem(40, 8);
+        let problem = Rc::new(problem);
+        let bound = gtsp_lower_bound(&problem, 1000);
+        let opt = gtsp_exact(problem).fitness;
+        assert!(bound > 0.0 && bound <= opt + 1e-9, "{} {}", bound, opt);
+    }
+}
@@ -0,0 +1,199 @@
+use std::rc::Rc;
+
+use rand::Rng;
+use rand_distr::{Normal, Distribution};
+
+use crate::opt_traits::*;
+use crate::opt_data::*;
+use crate::bound_handling::*;
+use crate::perturbe::*;
+use crate::termination::*;
+
+// Bounds of the real and integer segments and number of values of every categorical variable
+pub struct MixedSpec {
+    pub real_bounds: Vec<Bounds>,
+    pub int_bounds: Vec<IntBounds>,
+    pub categories_counts: Vec<usize>
+}
+
+// Genotype with real, integer and categorical segments, categories are indices into 0..categories_counts[i]
+#[derive(Clone)]
+pub struct MixedVec {
+    pub reals: Vec<f64>,
+    pub ints: Vec<i64>,
+    pub categories: Vec<usize>
+}
+
+impl OptData for MixedVec {
+    fn dim(&self) -> usize {
+        self.reals.len() + self.ints.len() + self.categories.len()
+    }
+}
+
+impl TerminationCond<MixedVec> for MaxIterTerminationCond {
+    fn eval(&self, iter: usize, _: f64) -> bool {
+        return iter >= self.n_iters;
+    }
+}
+
+impl MixedVec {
+    pub fn random(spec: &MixedSpec) -> Self {
+        let mut rng = rand::thread_rng();
+        MixedVec {
+            reals: spec.real_bounds.iter().map(|b| rng.gen_range(b.lower..=b.upper)).collect(),
+            ints: spec.int_bounds.iter().map(|b| rng.gen_range(b.lower..=b.upper)).collect(),
+            categories: spec.categories_counts.iter().map(|count| rng.gen_range(0..*count)).collect()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct InitRandomMixedVecPopulation {
+    pub size: usize,
+    pub spec: Rc<MixedSpec>
+}
+
+impl InitFunc<MixedVec> for InitRandomMixedVecPopulation {
+    fn init(&self) -> MixedVec {
+        MixedVec::random(&self.spec)
+    }
+}
+
+impl InitPopulation<MixedVec> for InitRandomMixedVecPopulation {
+    fn init(&self) -> Vec<MixedVec> {
+        (0..self.size).map(|_| MixedVec::random(&self.spec)).collect()
+    }
+}
+
+// Every gene is mutated with probability 1/dim, reals by a gaussian step relative to the
+// width of their bounds, integers by int_mutation and categories are reset to a different value
+#[derive(Clone)]
+pub struct MixedPerturbeMutOp {
+    pub spec: Rc<MixedSpec>,
+    pub real_sigma: f64,
+    // real_sigma follows the 1/5th success rule in update, it stays fixed otherwise
+    pub adapt_real_sigma: bool,
+    pub bound_handling: BoundHandling,
+    pub int_mutation: IntMutationType
+}
+
+impl PerturbeMutOp<MixedVec> for MixedPerturbeMutOp {
+    fn eval(&self, data: &mut MixedVec) {
+        let mut rng = rand::thread_rng();
+        let gene_prob = 1.0 / data.dim() as f64;
+        let normal = Normal::new(0.0, self.real_sigma).unwrap();
+        for i in 0..data.reals.len() {
+            if rng.gen::<f64>() < gene_prob {
+                let bounds = &self.spec.real_bounds[i];
+                let value = data.reals[i] + (bounds.upper - bounds.lower) * normal.sample(&mut rng);
+                data.reals[i] = handle_bound(value, data.reals[i], bounds, self.bound_handling);
+            }
+        }
+        for i in 0..data.ints.len() {
+            if rng.gen::<f64>() < gene_prob {
+                data.ints[i] = mutate_int(data.ints[i], &self.spec.int_bounds[i], self.int_mutation);
+            }
+        }
+        for i in 0..data.categories.len() {
+            let count = self.spec.categories_counts[i];
+            if count > 1 && rng.gen::<f64>() < gene_prob {
+                let shift = rng.gen_range(1..count);
+                data.categories[i] = (data.categories[i] + shift) % count;
+            }
+        }
+    }
+
+    fn update(&mut self, iter_diff: f64, dim: usize) {
+        if !self.adapt_real_sigma {
+            return;
+        }
+        self.real_sigma *= (if iter_diff < 0.0 { 1.0 } else { 0.0 } - 0.2f64).exp().powf(1.0 / (dim as f64));
+    }
+}
+
+// Uniform crossover of all segments, reals are additionally blended by a random weight
+pub struct MixedUniformCrossover {
+    pub blend_reals: bool
+}
+
+impl MixedUniformCrossover {
+    fn crossover_pair(&self, parents: [&MixedVec; 2]) -> [MixedVec; 2] {
+        let mut rng = rand::thread_rng();
+        let mut offsprings = [parents[0].clone(), parents[1].clone()];
+        for i in 0..parents[0].reals.len() {
+            if self.blend_reals {
+                let c = rng.gen::<f64>();
+                offsprings[0].reals[i] = c * parents[0].reals[i] + (1.0 - c) * parents[1].reals[i];
+                offsprings[1].reals[i] = (1.0 - c) * parents[0].reals[i] + c * parents[1].reals[i];
+            } else if rng.gen::<bool>() {
+                offsprings[0].reals[i] = parents[1].reals[i];
+                offsprings[1].reals[i] = parents[0].reals[i];
+            }
+        }
+        for i in 0..parents[0].ints.len() {
+            if rng.gen::<bool>() {
+                offsprings[0].ints[i] = parents[1].ints[i];
+                offsprings[1].ints[i] = parents[0].ints[i];
+            }
+        }
+        for i in 0..parents[0].categories.len() {
+            if rng.gen::<bool>() {
+                offsprings[0].categories[i] = parents[1].categories[i];
+                offsprings[1].categories[i] = parents[0].categories[i];
+            }
+        }
+        offsprings
+    }
+}
+
+impl Crossover<MixedVec> for MixedUniformCrossover {
+    fn crossover(&self, population: &Vec<MixedVec>, parents_indices: &Vec<usize>, offsprings: &mut Vec<MixedVec>) {
+        offsprings.clear();
+        for i in (0..parents_indices.len()).step_by(2) {
+            if i + 1 >= parents_indices.len() {
+                continue;
+            }
+            let parent1 = &population[parents_indices[i]];
+            let parent2 = &population[parents_indices[i + 1]];
+            let [offspring1, offspring2] = self.crossover_pair([parent1, parent2]);
+            offsprings.push(offspring1);
+            offsprings.push(offspring2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_mixed_operators_keep_bounds() {
+        let spec = Rc::new(MixedSpec {
+            real_bounds: vec![Bounds { lower: -1.0, upper: 1.0 }; 3],
+            int_bounds: vec![IntBounds { lower: -2, upper: 5 }; 3],
+            categories_counts: vec![2, 3, 4]
+        });
+        let init = InitRandomMixedVecPopulation { size: 10, spec: spec.clone() };
+        let mut population = InitPopulation::<MixedVec>::init(&init);
+        let mut offsprings = Vec::new();
+        let parents_indices: Vec<usize> = (0..population.len()).collect();
+        MixedUniformCrossover { blend_reals: true }.crossover(&population, &parents_indices[..9].to_vec(), &mut offsprings);
+        // the unpaired last parent is skipped
+        assert_eq!(offsprings.len(), 8);
+        MixedUniformCrossover { blend_reals: true }.crossover(&population, &parents_indices, &mut offsprings);
+        population.append(&mut offsprings);
+        let mut mutation = MixedPerturbeMutOp { spec: spec.clone(), real_sigma: 1.0, adapt_real_sigma: false, bound_handling: BoundHandling::Reflect,
+            int_mutation: IntMutationType::RoundedNormal { sigma: 3.0 } };
+        mutation.update(1.0, 9);
+        assert_eq!(mutation.real_sigma, 1.0);
+        for data in population.iter_mut() {
+            for _ in 0..50 {
+                mutation.eval(data);
+            }
+            assert!(data.reals.iter().all(|v| *v >= -1.0 && *v <= 1.0));
+            assert!(data.ints.iter().all(|v| *v >= -2 && *v <= 5));
+            assert!((0..3).all(|i| data.categories[i] < spec.categories_counts[i]));
+        }
+    }
+}
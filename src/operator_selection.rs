@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+
+use rand::Rng;
+
+use crate::opt_data::*;
+
+#[derive(Debug, Copy, Clone)]
+pub enum OperatorSelectionMethod {
+    // probabilities proportional to the estimated rewards, every operator keeps at least p_min
+    ProbabilityMatching { p_min: f64, alpha: f64 },
+    // Thierens' adaptive pursuit, probability of the best operator is pushed towards 1 - (K - 1) p_min
+    AdaptivePursuit { p_min: f64, alpha: f64, beta: f64 },
+    // upper confidence bound bandit using the mean reward of all previous applications
+    Ucb { c: f64 },
+    // upper confidence bound bandit using only the last `window` applications
+    SlidingWindowUcb { c: f64, window: usize }
+}
+
+// Learns which operator to apply from the rewards of its previous applications.
+// Selection probabilities are recorded by `record`, once per iteration, so they can be plotted.
+// Bandits select deterministically, their recorded probabilities are the selection frequencies.
+#[derive(Clone)]
+pub struct OperatorSelector {
+    pub method: OperatorSelectionMethod,
+    qualities: Vec<f64>,
+    probs: Vec<f64>,
+    counts: Vec<usize>,
+    reward_sums: Vec<f64>,
+    window: VecDeque<(usize, f64)>,
+    pub probs_history: Vec<Vec<f64>>
+}
+
+impl OperatorSelector {
+    pub fn new(method: OperatorSelectionMethod, ops_count: usize) -> Self {
+        OperatorSelector {
+            method,
+            qualities: vec![1.0; ops_count],
+            probs: vec![1.0 / ops_count as f64; ops_count],
+            counts: vec![0; ops_count],
+            reward_sums: vec![0.0; ops_count],
+            window: VecDeque::new(),
+            probs_history: Vec::new()
+        }
+    }
+
+    pub fn ops_count(&self) -> usize {
+        self.probs.len()
+    }
+
+    pub fn select(&self) -> usize {
+        match self.method {
+            OperatorSelectionMethod::ProbabilityMatching { .. } | OperatorSelectionMethod::AdaptivePursuit { .. } => {
+                let mut r = rand::random::<f64>();
+                for op in 0..self.probs.len() {
+                    if r < self.probs[op] {
+                        return op;
+                    }
+                    r -= self.probs[op];
+                }
+                self.probs.len() - 1
+            },
+            OperatorSelectionMethod::Ucb { c } | OperatorSelectionMethod::SlidingWindowUcb { c, .. } => {
+                // untried operators first, ties are broken randomly
+                let untried: Vec<usize> = (0..self.counts.len()).filter(|op| self.counts[*op] == 0).collect();
+                if !untried.is_empty() {
+                    return untried[rand::thread_rng().gen_range(0..untried.len())];
+                }
+                let total: usize = self.counts.iter().sum();
+                let mut best_op = 0;
+                let mut best_value = f64::NEG_INFINITY;
+                for op in 0..self.counts.len() {
+                    let n = self.counts[op] as f64;
+                    let value = self.reward_sums[op] / n + c * (2.0 * (total as f64).ln() / n).sqrt();
+                    if value > best_value {
+                        best_value = value;
+                        best_op = op;
+                    }
+                }
+                best_op
+            }
+        }
+    }
+
+    pub fn reward(&mut self, op: usize, reward: f64) {
+        let ops_count = self.ops_count() as f64;
+        match self.method {
+            OperatorSelectionMethod::ProbabilityMatching { p_min, alpha } => {
+                self.qualities[op] += alpha * (reward - self.qualities[op]);
+                let quality_sum: f64 = self.qualities.iter().sum();
+                for i in 0..self.probs.len() {
+                    self.probs[i] = if quality_sum > 0.0 {
+                        p_min + (1.0 - ops_count * p_min) * self.qualities[i] / quality_sum
+                    } else {
+                        1.0 / ops_count
+                    };
+                }
+            },
+            OperatorSelectionMethod::AdaptivePursuit { p_min, alpha, beta } => {
+                self.qualities[op] += alpha * (reward - self.qualities[op]);
+                let p_max = 1.0 - (ops_count - 1.0) * p_min;
+                let best_op = (0..self.qualities.len()).max_by(|a, b| self.qualities[*a].total_cmp(&self.qualities[*b])).unwrap();
+                for i in 0..self.probs.len() {
+                    let target = if i == best_op { p_max } else { p_min };
+                    self.probs[i] += beta * (target - self.probs[i]);
+                }
+            },
+            OperatorSelectionMethod::Ucb { .. } => {
+                self.counts[op] += 1;
+                self.reward_sums[op] += reward;
+            },
+            OperatorSelectionMethod::SlidingWindowUcb { window, .. } => {
+                self.counts[op] += 1;
+                self.reward_sums[op] += reward;
+                self.window.push_back((op, reward));
+                if self.window.len() > window {
+                    let (old_op, old_reward) = self.window.pop_front().unwrap();
+                    self.counts[old_op] -= 1;
+                    self.reward_sums[old_op] -= old_reward;
+                }
+            }
+        }
+    }
+
+    pub fn probs(&self) -> Vec<f64> {
+        match self.method {
+            OperatorSelectionMethod::ProbabilityMatching { .. } | OperatorSelectionMethod::AdaptivePursuit { .. } => self.probs.clone(),
+            OperatorSelectionMethod::Ucb { .. } | OperatorSelectionMethod::SlidingWindowUcb { .. } => {
+                let total: usize = self.counts.iter().sum();
+                if total == 0 {
+                    return vec![1.0 / self.ops_count() as f64; self.ops_count()];
+                }
+                self.counts.iter().map(|n| *n as f64 / total as f64).collect()
+            }
+        }
+    }
+
+    pub fn record(&mut self) {
+        self.probs_history.push(self.probs());
+    }
+
+    // probabilities of every operator over time in the format used by plot_multiple
+    pub fn probs_statistics(&self) -> Vec<BSFSingleObjStatistics> {
+        (0..self.ops_count())
+            .map(|op| BSFSingleObjStatistics { fitness: self.probs_history.iter().map(|probs| probs[op]).collect() })
+            .collect()
+    }
+}
+
+// relative improvement of the offspring over its parent, zero if it got worse
+pub fn improvement_reward(parent_fitness: f64, offspring_fitness: f64) -> f64 {
+    (parent_fitness - offspring_fitness).max(0.0) / parent_fitness.abs().max(1.0e-12)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn learns_best_operator(method: OperatorSelectionMethod) {
+        let mut selector = OperatorSelector::new(method, 3);
+        for _ in 0..2000 {
+            let op = selector.select();
+            let reward = if op == 1 { 1.0 } else { 0.1 * rand::random::<f64>() };
+            selector.reward(op, reward);
+        }
+        let probs = selector.probs();
+        assert!((probs.iter().sum::<f64>() - 1.0).abs() < 1.0e-9);
+        assert!(probs[1] > probs[0] && probs[1] > probs[2]);
+    }
+
+    #[test]
+    fn test_learns_best_operator() {
+        learns_best_operator(OperatorSelectionMethod::ProbabilityMatching { p_min: 0.05, alpha: 0.3 });
+        learns_best_operator(OperatorSelectionMethod::AdaptivePursuit { p_min: 0.05, alpha: 0.3, beta: 0.3 });
+        learns_best_operator(OperatorSelectionMethod::Ucb { c: 0.5 });
+        learns_best_operator(OperatorSelectionMethod::SlidingWindowUcb { c: 0.5, window: 100 });
+    }
+}
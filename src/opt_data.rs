@@ -18,6 +18,17 @@ pub struct NaiveBitVec {
     pub bits: Vec<u8>
 }
 
+#[derive(Debug, Copy, Clone)]
+pub struct IntBounds {
+    pub upper: i64,
+    pub lower: i64
+}
+
+#[derive(Clone)]
+pub struct IntVec {
+    pub values: Vec<i64>
+}
+
 impl OptData for FloatVec {
     fn dim(&self) -> usize {
         self.values.len()
@@ -30,6 +41,12 @@ impl OptData for NaiveBitVec {
     }
 }
 
+impl OptData for IntVec {
+    fn dim(&self) -> usize {
+        self.values.len()
+    }
+}
+
 pub trait VecOptData<T : Clone> {
     fn new() -> Self;
     fn with_capacity(capacity: usize) -> Self;
@@ -67,6 +84,21 @@ impl VecOptData<u8> for NaiveBitVec {
     }
 }
 
+impl VecOptData<i64> for IntVec {
+    fn new() -> Self {
+        IntVec { values: Vec::new() }
+    }
+    fn with_capacity(capacity: usize) -> Self {
+        IntVec { values: Vec::with_capacity(capacity) }
+    }
+    fn get(&self) -> &Vec<i64> {
+        &self.values
+    }
+    fn get_mut(&mut self) -> &mut Vec<i64> {
+        &mut self.values
+    }
+}
+
 pub trait ConstraintsSumed<T: OptData> {
     fn violations(&self, data: &T) -> Vec<f64>;
 }
@@ -196,4 +228,4 @@ impl<T: OptData> Statistics<T, f64, f64> for IterSingleObjStatistics {
         let best_index = find_best_fitness(fitness_opt);
         self.fitness.push(fitness_opt[best_index]);
     }
-}
\ No newline at end of file
+}
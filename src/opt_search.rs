@@ -43,6 +43,50 @@ pub fn local_search<
     (BSFSingleObjSolution::<T> { value: curr_value, fitness: curr_fitness }, stats)
 }
 
+// Local search computing the fitness of the perturbed value from the deltas of the applied moves
+pub fn local_search_delta<
+        T: OptData,
+        M,
+        FitnessFuncT : MoveFitnessFunc<T, M>,
+        InitFuncT: InitFunc<T>,
+        PerturbeMutOpT : MovePerturbeMutOp<T, M>,
+        TerminationCondT: TerminationCond<T>
+    >(
+        fitness: &mut FitnessFuncT,
+        init_func: InitFuncT,
+        mut perturbe_mut_op: PerturbeMutOpT,
+        termination_cond: &TerminationCondT
+    )
+    -> (BSFSingleObjSolution<T>, BSFSingleObjStatistics)
+{
+    let init_value = init_func.init();
+    let mut stats = BSFSingleObjStatistics { fitness: Vec::<f64>::new() };
+    let mut iter: usize = 0;
+    let mut diff = f64::INFINITY;
+    let mut curr_value = init_value.clone();
+    let mut curr_fitness = fitness.eval(&curr_value);
+    let mut next_value = init_value.clone();
+    stats.fitness.push(curr_fitness);
+    while !termination_cond.eval(iter, diff) {
+        next_value.clone_from(&curr_value);
+        let mut next_fitness = curr_fitness;
+        perturbe_mut_op.eval_moves(&mut next_value, &mut |data, mv| next_fitness += fitness.move_delta(data, mv));
+        diff = next_fitness - curr_fitness;
+        let is_better = next_fitness < curr_fitness;
+        perturbe_mut_op.update(diff, init_value.dim());
+        if is_better {
+            curr_value.clone_from(&next_value);
+            curr_fitness = next_fitness;
+        }
+        stats.fitness.push(curr_fitness);
+        iter += 1;
+    }
+    // the deltas accumulate rounding errors
+    curr_fitness = fitness.eval(&curr_value);
+    *stats.fitness.last_mut().unwrap() = curr_fitness;
+    (BSFSingleObjSolution::<T> { value: curr_value, fitness: curr_fitness }, stats)
+}
+
 pub fn local_search_evolutionary_api<
         T: OptData,
         FitnessFuncT : FitnessFunc<T>,
@@ -156,6 +200,74 @@ pub fn evolutionary_search<
 }
 
 
+// Evolutionary search without crossover, offsprings are perturbed copies of the selected parents
+// and their fitness is the fitness of the parent plus the deltas of the applied moves.
+// With IdentitySelection and a population of one it is a local search.
+pub fn evolutionary_search_delta<
+        T: OptData,
+        M,
+        FitnessFuncT : MoveFitnessFunc<T, M>,
+        InitPopulationT: InitPopulation<T>,
+        SelectionT: Selection<T, f64>,
+        PerturbeMutOpT: MovePerturbeMutOp<T, M>,
+        ReplacementStrategyT: ReplacementStrategy<T, f64, f64>,
+        TerminationCondT: TerminationCond<T>,
+        StatisticsT: Statistics<T, f64, f64>
+    >(
+        fitness_func: &mut FitnessFuncT,
+        init_population: InitPopulationT,
+        selection: &SelectionT,
+        mut perturbe_mut_op: PerturbeMutOpT,
+        replacement_strategy: &ReplacementStrategyT,
+        termination_cond: &TerminationCondT
+    )
+    -> (BSFSingleObjSolution<T>, StatisticsT)
+{
+    let mut population = InitPopulation::init(&init_population);
+    let mut fitness = Vec::<f64>::with_capacity(population.len());
+    // just so we can call replace
+    let mut fitness2 = Vec::<f64>::with_capacity(population.len());
+    let mut parents_indices = Vec::<usize>::new();
+    let mut offsprings = Vec::<T>::new();
+    let mut offsprings_fitness = Vec::<f64>::new();
+    fitness_func.eval_population(&population, &mut fitness);
+    let mut iter: usize = 0;
+    let mut diff = f64::INFINITY;
+    let mut best_index = find_best(&fitness);
+    let mut best_value = population[best_index].clone();
+    let mut best_fitness = fitness[best_index];
+    let mut stats = StatisticsT::new();
+    while !termination_cond.eval(iter, diff) {
+        selection.select(&fitness, &mut parents_indices);
+        for parent_index in &parents_indices {
+            let mut offspring = population[*parent_index].clone();
+            let mut offspring_fitness = fitness[*parent_index];
+            perturbe_mut_op.eval_moves(&mut offspring, &mut |data, mv| offspring_fitness += fitness_func.move_delta(data, mv));
+            offsprings.push(offspring);
+            offsprings_fitness.push(offspring_fitness);
+        }
+        let prev_best_fitness = fitness[best_index];
+        let offsprings_from = population.len();
+        join_populations(&mut population, &mut fitness, &mut offsprings, &mut offsprings_fitness);
+        fitness2.resize(fitness.len(), 0.0);
+        fitness2.copy_from_slice(&fitness);
+        replacement_strategy.replace(&mut population, &mut fitness, &mut fitness2, offsprings_from);
+        best_index = find_best(&fitness);
+        let curr_best_fitness = fitness[best_index];
+        if curr_best_fitness < best_fitness {
+            best_fitness = curr_best_fitness;
+            best_value = population[best_index].clone();
+        }
+        diff = curr_best_fitness - prev_best_fitness;
+        perturbe_mut_op.update(diff, population[0].dim());
+        stats.report_iter(iter, &population, &fitness, &fitness2);
+        iter += 1;
+    }
+    // the deltas accumulate rounding errors
+    best_fitness = fitness_func.eval(&best_value);
+    (BSFSingleObjSolution::<T> { value: best_value, fitness: best_fitness }, stats)
+}
+
 pub fn general_evolutionary_search<
         T: OptData,
         FIn: Fitness,
@@ -135,6 +135,17 @@ pub trait PerturbeMutOp<T: OptData> : Clone {
     fn update(&mut self, _iter_diff: f64, _dim: usize) {}
 }
 
+// Fitness which can compute the change caused by a move without evaluating the whole data
+pub trait MoveFitnessFunc<T: OptData, M> : FitnessFunc<T> {
+    // fitness after applying the move to data minus the fitness of data
+    fn move_delta(&self, data: &T, mv: &M) -> f64;
+}
+
+// Perturbation made of moves, on_move is called with the data before every move it applies
+pub trait MovePerturbeMutOp<T: OptData, M> : PerturbeMutOp<T> {
+    fn eval_moves(&self, data: &mut T, on_move: &mut dyn FnMut(&T, &M));
+}
+
 pub trait TerminationCond<T: OptData> {
     fn eval(&self, iter: usize, fitness: f64) -> bool;
 }
@@ -1,9 +1,13 @@
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
+use rand::Rng;
 use rand_distr::{Cauchy, Normal, Distribution};
 
 use crate::opt_traits::*;
 use crate::opt_data::*;
+use crate::bound_handling::*;
+use crate::operator_selection::*;
+use crate::bit_array::*;
 
 pub fn perturbe_mut(bits: &mut [u8], prob: f64) {
     for bit in bits.iter_mut() {
@@ -27,6 +31,68 @@ impl PerturbeMutOp<NaiveBitVec> for BasicNaiveBitPerturbeMutOp {
     }
 }
 
+// Bit-flip mutation of packed bits, without flip probability every bit is flipped with probability 1/n
+#[derive(Clone)]
+pub struct BitVecPerturbeMutOp {
+    pub flip_prob: Option<f64>
+}
+
+impl PerturbeMutOp<BitVec> for BitVecPerturbeMutOp {
+    fn eval(&self, data: &mut BitVec) {
+        let prob = self.flip_prob.unwrap_or(1.0 / data.len() as f64);
+        data.perturbe(prob);
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum IntMutationType {
+    // new uniformly random value inside the bounds
+    RandomReset,
+    // adds a nonzero uniformly random step in [-max_step, max_step]
+    Creep { max_step: i64 },
+    // adds a normally distributed step rounded to the nearest integer
+    RoundedNormal { sigma: f64 }
+}
+
+// mutated value is clamped into the bounds
+pub fn mutate_int(value: i64, bounds: &IntBounds, mutation: IntMutationType) -> i64 {
+    let mut rng = rand::thread_rng();
+    let new_value = match mutation {
+        IntMutationType::RandomReset => rng.gen_range(bounds.lower..=bounds.upper),
+        IntMutationType::Creep { max_step } => {
+            let step = rng.gen_range(1..=max_step.max(1));
+            if rng.gen::<bool>() { value + step } else { value - step }
+        },
+        IntMutationType::RoundedNormal { sigma } => value + Normal::new(0.0, sigma).unwrap().sample(&mut rng).round() as i64
+    };
+    new_value.clamp(bounds.lower, bounds.upper)
+}
+
+// Mutates every gene with gene_prob, without it with probability 1/n
+#[derive(Clone)]
+pub struct IntPerturbeMutOp {
+    pub mutation: IntMutationType,
+    pub gene_prob: Option<f64>,
+    pub bounds: Vec<IntBounds>
+}
+
+impl IntPerturbeMutOp {
+    pub fn new(mutation: IntMutationType, bounds: &Vec<IntBounds>) -> Self {
+        IntPerturbeMutOp { mutation, gene_prob: None, bounds: bounds.clone() }
+    }
+}
+
+impl PerturbeMutOp<IntVec> for IntPerturbeMutOp {
+    fn eval(&self, data: &mut IntVec) {
+        let gene_prob = self.gene_prob.unwrap_or(1.0 / data.values.len() as f64);
+        for i in 0..data.values.len() {
+            if rand::random::<f64>() < gene_prob {
+                data.values[i] = mutate_int(data.values[i], &self.bounds[i], self.mutation);
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct NormalPerturbeRealMutOp {
     normal: Normal<f64>
@@ -49,21 +115,27 @@ impl PerturbeMutOp<FloatVec> for NormalPerturbeRealMutOp {
 #[derive(Clone)]
 pub struct BoundedNormalPerturbeRealMutOp {
     normal: Normal<f64>,
-    bounds: Vec<Bounds>
+    bounds: Vec<Bounds>,
+    bound_handling: BoundHandling
 }
 
 impl BoundedNormalPerturbeRealMutOp {
     pub fn new(sigma: f64, bounds: &Vec<Bounds>) -> Self {
-        BoundedNormalPerturbeRealMutOp { normal: Normal::new(0.0, sigma).unwrap(), bounds: bounds.clone() }
+        Self::new_with_handling(sigma, bounds, BoundHandling::Clamp)
+    }
+
+    pub fn new_with_handling(sigma: f64, bounds: &Vec<Bounds>, bound_handling: BoundHandling) -> Self {
+        BoundedNormalPerturbeRealMutOp { normal: Normal::new(0.0, sigma).unwrap(), bounds: bounds.clone(), bound_handling }
     }
 }
 
 impl PerturbeMutOp<FloatVec> for BoundedNormalPerturbeRealMutOp {
     fn eval(&self, data: &mut FloatVec) {
         for i in 0..data.values.len() {
-            data.values[i] = 
-                (data.values[i] + self.normal.sample(&mut rand::thread_rng()))
-                .clamp(self.bounds[i].lower, self.bounds[i].upper);
+            let parent = data.values[i];
+            data.values[i] = handle_bound(
+                parent + self.normal.sample(&mut rand::thread_rng()),
+                parent, &self.bounds[i], self.bound_handling);
         }
     }
 }
@@ -95,21 +167,27 @@ impl PerturbeMutOp<FloatVec> for NormalOneFiftPerturbeRealMutOp {
 #[derive(Clone)]
 pub struct BoundedNormalOneFiftPerturbeRealMutOp {
     normal: Normal<f64>,
-    bounds: Vec<Bounds>
+    bounds: Vec<Bounds>,
+    bound_handling: BoundHandling
 }
 
 impl BoundedNormalOneFiftPerturbeRealMutOp {
     pub fn new(sigma: f64, bounds: &Vec<Bounds>) -> Self {
-        BoundedNormalOneFiftPerturbeRealMutOp { normal: Normal::new(0.0, sigma).unwrap(), bounds: bounds.clone() }
+        Self::new_with_handling(sigma, bounds, BoundHandling::Clamp)
+    }
+
+    pub fn new_with_handling(sigma: f64, bounds: &Vec<Bounds>, bound_handling: BoundHandling) -> Self {
+        BoundedNormalOneFiftPerturbeRealMutOp { normal: Normal::new(0.0, sigma).unwrap(), bounds: bounds.clone(), bound_handling }
     }
 }
 
 impl PerturbeMutOp<FloatVec> for BoundedNormalOneFiftPerturbeRealMutOp {
     fn eval(&self, data: &mut FloatVec) {
         for i in 0..data.values.len() {
-            data.values[i] = 
-                (data.values[i] + self.normal.sample(&mut rand::thread_rng()))
-                .clamp(self.bounds[i].lower, self.bounds[i].upper);
+            let parent = data.values[i];
+            data.values[i] = handle_bound(
+                parent + self.normal.sample(&mut rand::thread_rng()),
+                parent, &self.bounds[i], self.bound_handling);
         }
     }
 
@@ -138,6 +216,111 @@ impl PerturbeMutOp<FloatVec> for CauchyPerturbeRealMutOp {
     }
 }
 
+// Deb's polynomial mutation, perturbation is scaled by the bounds size and never leaves the bounds
+#[derive(Clone)]
+pub struct PolynomialPerturbeRealMutOp {
+    eta: f64,
+    gene_prob: f64,
+    bounds: Vec<Bounds>
+}
+
+impl PolynomialPerturbeRealMutOp {
+    pub fn new(eta: f64, gene_prob: f64, bounds: &Vec<Bounds>) -> Self {
+        PolynomialPerturbeRealMutOp { eta, gene_prob, bounds: bounds.clone() }
+    }
+}
+
+impl PerturbeMutOp<FloatVec> for PolynomialPerturbeRealMutOp {
+    fn eval(&self, data: &mut FloatVec) {
+        let mut_pow = 1.0 / (self.eta + 1.0);
+        for i in 0..data.values.len() {
+            if rand::random::<f64>() >= self.gene_prob {
+                continue;
+            }
+            let (lower, upper) = (self.bounds[i].lower, self.bounds[i].upper);
+            let size = upper - lower;
+            if size <= 0.0 {
+                continue;
+            }
+            let y = data.values[i];
+            let r = rand::random::<f64>();
+            let delta_q = if r < 0.5 {
+                let xy = 1.0 - (y - lower) / size;
+                let val = 2.0 * r + (1.0 - 2.0 * r) * xy.powf(self.eta + 1.0);
+                val.powf(mut_pow) - 1.0
+            } else {
+                let xy = 1.0 - (upper - y) / size;
+                let val = 2.0 * (1.0 - r) + 2.0 * (r - 0.5) * xy.powf(self.eta + 1.0);
+                1.0 - val.powf(mut_pow)
+            };
+            data.values[i] = (y + delta_q * size).clamp(lower, upper);
+        }
+    }
+}
+
+// Michalewicz's non-uniform mutation, the perturbation shrinks towards zero as the generations pass
+#[derive(Clone)]
+pub struct NonUniformPerturbeRealMutOp {
+    // degree of dependency on the generation number
+    b: f64,
+    max_generations: usize,
+    generation: usize,
+    gene_prob: f64,
+    bounds: Vec<Bounds>
+}
+
+impl NonUniformPerturbeRealMutOp {
+    pub fn new(b: f64, max_generations: usize, gene_prob: f64, bounds: &Vec<Bounds>) -> Self {
+        NonUniformPerturbeRealMutOp { b, max_generations, generation: 0, gene_prob, bounds: bounds.clone() }
+    }
+
+    fn delta(&self, y: f64) -> f64 {
+        let progress = (self.generation as f64 / self.max_generations as f64).min(1.0);
+        y * (1.0 - rand::random::<f64>().powf((1.0 - progress).powf(self.b)))
+    }
+}
+
+impl PerturbeMutOp<FloatVec> for NonUniformPerturbeRealMutOp {
+    fn eval(&self, data: &mut FloatVec) {
+        for i in 0..data.values.len() {
+            if rand::random::<f64>() >= self.gene_prob {
+                continue;
+            }
+            let value = data.values[i];
+            data.values[i] = if rand::random::<bool>() {
+                value + self.delta(self.bounds[i].upper - value)
+            } else {
+                value - self.delta(value - self.bounds[i].lower)
+            };
+        }
+    }
+
+    // called once per generation by the search loops
+    fn update(&mut self, _iter_diff: f64, _dim: usize) {
+        self.generation += 1;
+    }
+}
+
+// Applies bound handling after any FloatVec mutation, including the unbounded ones
+#[derive(Clone)]
+pub struct BoundedPerturbeMutOp<PerturbeMutOpT: PerturbeMutOp<FloatVec>> {
+    pub op: PerturbeMutOpT,
+    pub bounds: Vec<Bounds>,
+    pub bound_handling: BoundHandling
+}
+
+impl<PerturbeMutOpT: PerturbeMutOp<FloatVec>> PerturbeMutOp<FloatVec> for BoundedPerturbeMutOp<PerturbeMutOpT> {
+    fn eval(&self, data: &mut FloatVec) {
+        let parent = data.values.clone();
+        self.op.eval(data);
+        handle_bounds(&mut data.values, &parent, &self.bounds, self.bound_handling);
+    }
+
+    fn update(&mut self, iter_diff: f64, dim: usize) {
+        self.op.update(iter_diff, dim);
+    }
+}
+
 pub trait NoClonePerturbeMutOp<T: OptData> {
     fn eval_no_clone(&self, data: &mut T);
 }
@@ -167,4 +350,115 @@ impl<T: OptData> PerturbeMutOp<T> for CombinePerturbeMutOps<T> {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+pub trait NoCloneMovePerturbeMutOp<T: OptData, M> {
+    fn eval_moves_no_clone(&self, data: &mut T, on_move: &mut dyn FnMut(&T, &M));
+}
+
+impl<T: OptData, M, MovePerturbeMutOpT : MovePerturbeMutOp<T, M>> NoCloneMovePerturbeMutOp<T, M> for MovePerturbeMutOpT {
+    fn eval_moves_no_clone(&self, data: &mut T, on_move: &mut dyn FnMut(&T, &M)) {
+        self.eval_moves(data, on_move)
+    }
+}
+
+#[derive(Clone)]
+pub struct ProbMovePerturbeMutOp<T: OptData, M> {
+    pub prob: f64,
+    pub op: Rc<dyn NoCloneMovePerturbeMutOp<T, M>>
+}
+
+// CombinePerturbeMutOps reporting the moves of the combined operators
+#[derive(Clone)]
+pub struct CombineMovePerturbeMutOps<T: OptData, M> {
+    pub mut_ops: Vec<ProbMovePerturbeMutOp<T, M>>
+}
+
+impl<T: OptData, M: Clone> PerturbeMutOp<T> for CombineMovePerturbeMutOps<T, M> {
+    fn eval(&self, data: &mut T) {
+        self.eval_moves(data, &mut |_, _| {});
+    }
+}
+
+impl<T: OptData, M: Clone> MovePerturbeMutOp<T, M> for CombineMovePerturbeMutOps<T, M> {
+    fn eval_moves(&self, data: &mut T, on_move: &mut dyn FnMut(&T, &M)) {
+        for mut_op in &self.mut_ops {
+            if rand::random::<f64>() < mut_op.prob {
+                mut_op.op.eval_moves_no_clone(data, on_move);
+            }
+        }
+    }
+}
+
+// Applies one operator chosen by the selector, which learns from the improvement of the mutated individual.
+// The search loops don't pass the fitness of the individual before the mutation, so the reward costs
+// two extra evaluations of fitness_func per mutation on top of the evaluation done by the search.
+// Selector is shared by the clones, keep a reference to it to read the learned probabilities.
+#[derive(Clone)]
+pub struct AdaptiveCombinePerturbeMutOps<T: OptData> {
+    pub mut_ops: Vec<Rc<dyn NoClonePerturbeMutOp<T>>>,
+    pub fitness_func: Rc<dyn FitnessFunc<T>>,
+    pub selector: Rc<RefCell<OperatorSelector>>
+}
+
+impl<T: OptData> AdaptiveCombinePerturbeMutOps<T> {
+    pub fn new(mut_ops: Vec<Rc<dyn NoClonePerturbeMutOp<T>>>, fitness_func: Rc<dyn FitnessFunc<T>>, method: OperatorSelectionMethod) -> Self {
+        let selector = Rc::new(RefCell::new(OperatorSelector::new(method, mut_ops.len())));
+        AdaptiveCombinePerturbeMutOps { mut_ops, fitness_func, selector }
+    }
+}
+
+impl<T: OptData> PerturbeMutOp<T> for AdaptiveCombinePerturbeMutOps<T> {
+    fn eval(&self, data: &mut T) {
+        let parent_fitness = self.fitness_func.eval(data);
+        let op = self.selector.borrow().select();
+        self.mut_ops[op].eval_no_clone(data);
+        let offspring_fitness = self.fitness_func.eval(data);
+        self.selector.borrow_mut().reward(op, improvement_reward(parent_fitness, offspring_fitness));
+    }
+
+    fn update(&mut self, _iter_diff: f64, _dim: usize) {
+        self.selector.borrow_mut().record();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn mean_step<PerturbeMutOpT: PerturbeMutOp<FloatVec>>(op: &PerturbeMutOpT, bounds: &[Bounds]) -> f64 {
+        let mut sum = 0.0;
+        for _ in 0..1000 {
+            let original = FloatVec { values: vec![0.0; bounds.len()] };
+            let mut data = original.clone();
+            op.eval(&mut data);
+            for (i, value) in data.values.iter().enumerate() {
+                assert!(*value >= bounds[i].lower && *value <= bounds[i].upper);
+                sum += (value - original.values[i]).abs();
+            }
+        }
+        sum / (1000 * bounds.len()) as f64
+    }
+
+    #[test]
+    fn test_bounded_real_mutations() {
+        let bounds = vec![Bounds { lower: -1.0, upper: 1.0 }, Bounds { lower: -0.1, upper: 5.0 }, Bounds { lower: -10.0, upper: 0.5 }];
+        assert!(mean_step(&PolynomialPerturbeRealMutOp::new(20.0, 1.0, &bounds), &bounds) > 0.0);
+        assert_eq!(mean_step(&PolynomialPerturbeRealMutOp::new(20.0, 0.0, &bounds), &bounds), 0.0);
+        assert_eq!(mean_step(&NonUniformPerturbeRealMutOp::new(2.0, 100, 0.0, &bounds), &bounds), 0.0);
+
+        let mut non_uniform = NonUniformPerturbeRealMutOp::new(2.0, 100, 1.0, &bounds);
+        let mut prev_step = mean_step(&non_uniform, &bounds);
+        for _ in 0..2 {
+            for _ in 0..50 {
+                non_uniform.update(0.0, bounds.len());
+            }
+            let step = mean_step(&non_uniform, &bounds);
+            assert!(step < prev_step, "{} {}", step, prev_step);
+            prev_step = step;
+        }
+        // no perturbation after the last generation
+        assert_eq!(prev_step, 0.0);
+    }
+}
@@ -0,0 +1,248 @@
+use rand::Rng;
+
+use crate::opt_traits::*;
+use crate::opt_data::*;
+use crate::bound_handling::*;
+
+#[derive(Debug, Copy, Clone)]
+pub enum PsoTopology {
+    // every particle is informed by the best particle of the whole swarm
+    Global,
+    // particles are informed by `k` neighbours on each side of a ring
+    Ring(usize),
+    // particles are placed on a toroidal grid and informed by the 4 grid neighbours, the number
+    // of columns is the largest divisor of the swarm size not above its square root, so a prime
+    // swarm size gives a single column
+    VonNeumann
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum PsoVelocityUpdate {
+    // inertia weight is multiplied by `damping` after every iteration
+    Inertia { weight: f64, damping: f64, c1: f64, c2: f64 },
+    // Clerc's constriction factor, requires c1 + c2 > 4
+    Constriction { c1: f64, c2: f64 }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum PsoBoundaryHandling {
+    // particle stops at the bound and loses its velocity
+    Absorb,
+    // particle bounces back from the bound and its velocity is reversed
+    Reflect,
+    // particle is moved to a random position inside the bounds
+    Random
+}
+
+#[derive(Clone)]
+pub struct ParticleSwarmParams {
+    pub swarm_size: usize,
+    pub topology: PsoTopology,
+    pub velocity_update: PsoVelocityUpdate,
+    pub boundary_handling: PsoBoundaryHandling,
+    // maximal velocity relative to the size of the bounds
+    pub max_velocity_ratio: f64,
+    pub bounds: Vec<Bounds>
+}
+
+impl ParticleSwarmParams {
+    pub fn new(swarm_size: usize, bounds: &Vec<Bounds>) -> Self {
+        ParticleSwarmParams {
+            swarm_size,
+            topology: PsoTopology::Global,
+            velocity_update: PsoVelocityUpdate::Constriction { c1: 2.05, c2: 2.05 },
+            boundary_handling: PsoBoundaryHandling::Absorb,
+            max_velocity_ratio: 0.5,
+            bounds: bounds.clone()
+        }
+    }
+}
+
+pub fn pso_constriction_factor(c1: f64, c2: f64) -> f64 {
+    let phi = c1 + c2;
+    if phi <= 4.0 {
+        panic!("constriction factor requires c1 + c2 > 4");
+    }
+    2.0 / (2.0 - phi - (phi * phi - 4.0 * phi).sqrt()).abs()
+}
+
+fn pso_neighbours(topology: PsoTopology, swarm_size: usize) -> Vec<Vec<usize>> {
+    let mut neighbours = vec![Vec::<usize>::new(); swarm_size];
+    match topology {
+        PsoTopology::Global => {
+            for i in 0..swarm_size {
+                neighbours[i] = (0..swarm_size).collect();
+            }
+        },
+        PsoTopology::Ring(k) => {
+            let k = k.min(swarm_size / 2);
+            for i in 0..swarm_size {
+                neighbours[i].push(i);
+                for offset in 1..=k {
+                    neighbours[i].push((i + offset) % swarm_size);
+                    neighbours[i].push((i + swarm_size - offset) % swarm_size);
+                }
+            }
+        },
+        PsoTopology::VonNeumann => {
+            let cols = (1..=((swarm_size as f64).sqrt().floor() as usize).max(1))
+                .rev()
+                .find(|cols| swarm_size.is_multiple_of(*cols))
+                .unwrap();
+            for i in 0..swarm_size {
+                let (row, col) = (i / cols, i % cols);
+                // grids with fewer than 3 rows or columns would list some neighbours twice
+                for j in [i, row * cols + (col + 1) % cols, row * cols + (col + cols - 1) % cols,
+                        (i + cols) % swarm_size, (i + swarm_size - cols) % swarm_size] {
+                    if !neighbours[i].contains(&j) {
+                        neighbours[i].push(j);
+                    }
+                }
+            }
+        }
+    }
+    neighbours
+}
+
+fn pso_handle_boundary(position: &mut f64, velocity: &mut f64, bounds: &Bounds, boundary_handling: PsoBoundaryHandling) {
+    if *position >= bounds.lower && *position <= bounds.upper {
+        return;
+    }
+    match boundary_handling {
+        PsoBoundaryHandling::Absorb => {
+            *position = position.clamp(bounds.lower, bounds.upper);
+            *velocity = 0.0;
+        },
+        PsoBoundaryHandling::Reflect => {
+            *position = reflect_into_bounds(*position, bounds);
+            *velocity = -*velocity;
+        },
+        PsoBoundaryHandling::Random => {
+            *position = rand::thread_rng().gen_range(bounds.lower..=bounds.upper);
+        }
+    }
+}
+
+pub fn particle_swarm_search<
+        FitnessFuncT : FitnessFunc<FloatVec>,
+        InitPopulationT: InitPopulation<FloatVec>,
+        TerminationCondT: TerminationCond<FloatVec>,
+        StatisticsT: Statistics<FloatVec, f64, f64>
+    >(
+        fitness_func: &mut FitnessFuncT,
+        init_population: InitPopulationT,
+        params: &ParticleSwarmParams,
+        termination_cond: &TerminationCondT
+    )
+    -> (BSFSingleObjSolution<FloatVec>, StatisticsT)
+{
+    let mut positions = InitPopulation::init(&init_population);
+    positions.truncate(params.swarm_size);
+    let swarm_size = positions.len();
+    let dim = positions[0].dim();
+    let bounds = &params.bounds;
+    let max_velocity: Vec<f64> = (0..dim)
+        .map(|d| if d < bounds.len() { params.max_velocity_ratio * (bounds[d].upper - bounds[d].lower) } else { f64::INFINITY })
+        .collect();
+
+    let mut velocities = Vec::<FloatVec>::with_capacity(swarm_size);
+    for _ in 0..swarm_size {
+        let mut velocity = FloatVec { values: vec![0.0; dim] };
+        for d in 0..dim {
+            if max_velocity[d].is_finite() {
+                velocity.values[d] = rand::thread_rng().gen_range(-max_velocity[d]..=max_velocity[d]);
+            }
+        }
+        velocities.push(velocity);
+    }
+
+    let mut fitness = Vec::<f64>::with_capacity(swarm_size);
+    fitness_func.eval_population(&positions, &mut fitness);
+    let mut best_positions = positions.clone();
+    let mut best_fitness = fitness.clone();
+    let neighbours = pso_neighbours(params.topology, swarm_size);
+    let mut informants = vec![0usize; swarm_size];
+
+    let (mut inertia, damping, c1, c2) = match params.velocity_update {
+        PsoVelocityUpdate::Inertia { weight, damping, c1, c2 } => (weight, damping, c1, c2),
+        PsoVelocityUpdate::Constriction { c1, c2 } => (1.0, 1.0, c1, c2)
+    };
+    let constriction = match params.velocity_update {
+        PsoVelocityUpdate::Inertia { .. } => 1.0,
+        PsoVelocityUpdate::Constriction { c1, c2 } => pso_constriction_factor(c1, c2)
+    };
+
+    let mut best_index = find_best_fitness(&best_fitness);
+    let mut iter: usize = 0;
+    let mut diff = f64::INFINITY;
+    let mut stats = StatisticsT::new();
+    while !termination_cond.eval(iter, diff) {
+        for i in 0..swarm_size {
+            informants[i] = neighbours[i][0];
+            for n in &neighbours[i] {
+                if best_fitness[*n] < best_fitness[informants[i]] {
+                    informants[i] = *n;
+                }
+            }
+        }
+        for i in 0..swarm_size {
+            let local_best = &best_positions[informants[i]].values;
+            let personal_best = &best_positions[i].values;
+            for d in 0..dim {
+                let r1 = rand::random::<f64>();
+                let r2 = rand::random::<f64>();
+                let x = positions[i].values[d];
+                let mut v = constriction * (inertia * velocities[i].values[d]
+                    + c1 * r1 * (personal_best[d] - x)
+                    + c2 * r2 * (local_best[d] - x));
+                v = v.clamp(-max_velocity[d], max_velocity[d]);
+                let mut new_x = x + v;
+                if d < bounds.len() {
+                    pso_handle_boundary(&mut new_x, &mut v, &bounds[d], params.boundary_handling);
+                }
+                positions[i].values[d] = new_x;
+                velocities[i].values[d] = v;
+            }
+        }
+        fitness_func.eval_population(&positions, &mut fitness);
+        let prev_best_fitness = best_fitness[best_index];
+        for i in 0..swarm_size {
+            if fitness[i] < best_fitness[i] {
+                best_fitness[i] = fitness[i];
+                best_positions[i].clone_from(&positions[i]);
+            }
+        }
+        best_index = find_best_fitness(&best_fitness);
+        diff = best_fitness[best_index] - prev_best_fitness;
+        inertia *= damping;
+        stats.report_iter(iter, &best_positions, &best_fitness, &best_fitness);
+        iter += 1;
+    }
+    (BSFSingleObjSolution::<FloatVec> { value: best_positions[best_index].clone(), fitness: best_fitness[best_index] }, stats)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_von_neumann_grid() {
+        // 3 columns and 4 rows
+        let neighbours = pso_neighbours(PsoTopology::VonNeumann, 12);
+        assert_eq!(neighbours[2], vec![2, 0, 1, 5, 11]);
+        assert_eq!(neighbours[3], vec![3, 4, 5, 6, 0]);
+        // a prime size is a single column ring
+        assert_eq!(pso_neighbours(PsoTopology::VonNeumann, 7)[0], vec![0, 1, 6]);
+        for swarm_size in [1, 2, 4, 6, 7, 12] {
+            let neighbours = pso_neighbours(PsoTopology::VonNeumann, swarm_size);
+            for (i, particle_neighbours) in neighbours.iter().enumerate() {
+                assert!(particle_neighbours.iter().all(|j| neighbours[*j].contains(&i)));
+                let mut distinct = particle_neighbours.clone();
+                distinct.sort();
+                distinct.dedup();
+                assert_eq!(distinct.len(), particle_neighbours.len());
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::{fs::create_dir_all, rc::Rc};
+
+use crate::*;
+
+fn pso_comparison_graph<FitnessFuncT: FitnessFunc<FloatVec>>(
+    fitness: &mut FitnessFuncT,
+    bounds: &Vec<Bounds>,
+    opt_value: f64,
+    func_name: &str,
+    num_repetitions: usize,
+    num_iters: usize,
+    population_size: usize)
+{
+    let method_names = vec!["gbest constriction", "ring constriction", "von Neumann constriction", "gbest inertia", "ring inertia"];
+    let mut mean = 0.0;
+    let mut val_range = 0.0;
+    for bound in bounds {
+        mean += 0.5 * (bound.upper + bound.lower);
+        val_range += bound.upper - bound.lower;
+    }
+    mean /= bounds.len() as f64;
+    val_range /= bounds.len() as f64;
+
+    let init_population = InitRandomFloatVecPopulation {
+        size: population_size, vec_size: bounds.len(), mean, std_dev: 0.3 * val_range, bounds: bounds.clone(), bound_handling: BoundHandling::Reflect
+    };
+    let termination_cond = MaxIterTerminationCond { n_iters: num_iters };
+    let constriction = PsoVelocityUpdate::Constriction { c1: 2.05, c2: 2.05 };
+    let inertia = PsoVelocityUpdate::Inertia { weight: 0.9, damping: 0.999, c1: 1.5, c2: 1.5 };
+    let topologies = [
+        (PsoTopology::Global, constriction),
+        (PsoTopology::Ring(1), constriction),
+        (PsoTopology::VonNeumann, constriction),
+        (PsoTopology::Global, inertia),
+        (PsoTopology::Ring(1), inertia)
+    ];
+
+    let mut avg_stats = vec![BSFSingleObjStatistics { fitness: vec![0.0f64; num_iters]}; method_names.len()];
+    for _rep in 0..num_repetitions {
+        for m in 0..topologies.len() {
+            let mut params = ParticleSwarmParams::new(population_size, bounds);
+            params.topology = topologies[m].0;
+            params.velocity_update = topologies[m].1;
+            params.boundary_handling = PsoBoundaryHandling::Reflect;
+            let (_, stats) : (BSFSingleObjSolution<FloatVec>, BSFSingleObjStatistics) = particle_swarm_search(
+                fitness,
+                init_population.clone(),
+                &params,
+                &termination_cond);
+            for i in 0..num_iters {
+                avg_stats[m].fitness[i] += stats.fitness[i];
+            }
+        }
+    }
+    let log_opt_value = process_avg_stats(&mut avg_stats, opt_value, num_iters, num_repetitions);
+    plot_multiple(&avg_stats, &method_names, &TAB_COLORS, format!("out/pso/{}.svg", func_name).as_str(), func_name, log_opt_value, "Log avg. fitness", true, true).unwrap();
+}
+
+pub fn create_pso_comparison_graphs(num_repetitions: usize, num_iters: usize, population_size: usize)
+{
+    const DIM: usize = 10;
+    create_dir_all("out/pso").unwrap();
+
+    let mut sphere = SphereFunc { o: vec![0.0; DIM] };
+    let sphere_bounds = vec![Bounds { lower: -5.0, upper: 5.0 }; DIM];
+    pso_comparison_graph(&mut sphere, &sphere_bounds, 0.0, "sphere", num_repetitions, num_iters, population_size);
+
+    let mut rastrigin = RastriginFunc {};
+    let rastrigin_bounds = vec![Bounds { lower: -5.12, upper: 5.12 }; DIM];
+    pso_comparison_graph(&mut rastrigin, &rastrigin_bounds, 0.0, "rastrigin", num_repetitions, num_iters, population_size);
+
+    let mut schwefel = SchwefelFunc {};
+    let schwefel_bounds = vec![Bounds { lower: -500.0, upper: 500.0 }; DIM];
+    pso_comparison_graph(&mut schwefel, &schwefel_bounds, -418.9829 * (DIM as f64), "schwefel", num_repetitions, num_iters, population_size);
+
+    let g09 = GFuncDyn { func: Rc::new(G09 {}) };
+    let g09_bounds = g09.bounds();
+    let g09_opt_value = g09.eval(&g09.optimum());
+    let mut g09_penalty = PenaltyFunc::new(g09, 1.0e6);
+    pso_comparison_graph(&mut g09_penalty, &g09_bounds, g09_opt_value, "g09_penalty", num_repetitions, num_iters, population_size);
+}
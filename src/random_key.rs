@@ -0,0 +1,135 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::opt_traits::*;
+use crate::opt_data::*;
+use crate::tsp::*;
+use crate::gtsp::*;
+
+// Random-key encoding of permutations, the tour visits vertices (or groups) in the
+// ascending order of their keys so any real vector decodes to a valid permutation.
+
+// indices of keys sorted in ascending order, ties keep the index order
+fn sorted_key_indices(keys: &[f64], res: &mut Vec<usize>) {
+    res.clear();
+    res.extend(0..keys.len());
+    res.sort_by(|a, b| keys[*a].total_cmp(&keys[*b]));
+}
+
+pub fn random_key_decode_tsp(keys: &[f64], vert_perm: &mut Vec<usize>) {
+    sorted_key_indices(keys, vert_perm);
+}
+
+// vertex on the i-th position gets key (i + 0.5) / n
+pub fn random_key_encode_tsp(perm: &TspPermutation) -> FloatVec {
+    let n = perm.vert_perm.len();
+    let mut keys = vec![0.0; n];
+    for i in 0..n {
+        keys[perm.vert_perm[i]] = (i as f64 + 0.5) / n as f64;
+    }
+    FloatVec { values: keys }
+}
+
+// One key per group, groups are ordered by the whole key and the fractional part
+// selects the vertex in the group
+pub fn random_key_decode_gtsp(keys: &[f64], groups: &[Vec<usize>], perm: &mut Vec<GroupVert>) {
+    let mut order = Vec::with_capacity(keys.len());
+    sorted_key_indices(keys, &mut order);
+    perm.clear();
+    for group in order {
+        let size = groups[group].len();
+        let fract = keys[group] - keys[group].floor();
+        let vert = ((fract * size as f64) as usize).min(size - 1);
+        perm.push(GroupVert { group, vert });
+    }
+}
+
+// group on the i-th position gets key i + (vert + 0.5) / group size
+pub fn random_key_encode_gtsp(perm: &GtspPermutation) -> FloatVec {
+    let mut keys = vec![0.0; perm.perm.len()];
+    for i in 0..perm.perm.len() {
+        let gvert = perm.perm[i];
+        let size = perm.spec.groups[gvert.group].len();
+        keys[gvert.group] = i as f64 + (gvert.vert as f64 + 0.5) / size as f64;
+    }
+    FloatVec { values: keys }
+}
+
+// Evaluates random keys with a TSP fitness, so the FloatVec operators can be used on TSP
+pub struct RandomKeyTspFunc<TspFunc: FitnessFunc<TspPermutation>> {
+    pub tsp_func: TspFunc,
+    temp_data: RefCell<TspPermutation>
+}
+
+impl<TspFunc: FitnessFunc<TspPermutation>> RandomKeyTspFunc<TspFunc> {
+    pub fn new(tsp_func: TspFunc) -> Self {
+        RandomKeyTspFunc { tsp_func, temp_data: RefCell::new(TspPermutation { vert_perm: Vec::new() }) }
+    }
+
+    pub fn decode(&self, data: &FloatVec) -> TspPermutation {
+        let mut perm = TspPermutation { vert_perm: Vec::with_capacity(data.values.len()) };
+        random_key_decode_tsp(&data.values, &mut perm.vert_perm);
+        perm
+    }
+}
+
+impl<TspFunc: FitnessFunc<TspPermutation>> FitnessFunc<FloatVec> for RandomKeyTspFunc<TspFunc> {
+    fn eval(&self, data: &FloatVec) -> f64 {
+        random_key_decode_tsp(&data.values, &mut self.temp_data.borrow_mut().vert_perm);
+        self.tsp_func.eval(&self.temp_data.borrow())
+    }
+}
+
+// Evaluates random keys with a GTSP fitness, keys has one value per group
+pub struct RandomKeyGtspFunc<GtspFunc: FitnessFunc<GtspPermutation>> {
+    pub gtsp_func: GtspFunc,
+    pub spec: Rc<GtspProblem>,
+    temp_data: RefCell<GtspPermutation>
+}
+
+impl<GtspFunc: FitnessFunc<GtspPermutation>> RandomKeyGtspFunc<GtspFunc> {
+    pub fn new(gtsp_func: GtspFunc, spec: Rc<GtspProblem>) -> Self {
+        let temp_data = RefCell::new(GtspPermutation { spec: spec.clone(), perm: Vec::new() });
+        RandomKeyGtspFunc { gtsp_func, spec, temp_data }
+    }
+
+    pub fn decode(&self, data: &FloatVec) -> GtspPermutation {
+        let mut perm = GtspPermutation { spec: self.spec.clone(), perm: Vec::with_capacity(data.values.len()) };
+        random_key_decode_gtsp(&data.values, &self.spec.groups, &mut perm.perm);
+        perm
+    }
+}
+
+impl<GtspFunc: FitnessFunc<GtspPermutation>> FitnessFunc<FloatVec> for RandomKeyGtspFunc<GtspFunc> {
+    fn eval(&self, data: &FloatVec) -> f64 {
+        random_key_decode_gtsp(&data.values, &self.spec.groups, &mut self.temp_data.borrow_mut().perm);
+        self.gtsp_func.eval(&self.temp_data.borrow())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let tsp_perm = TspPermutation { vert_perm: vec![3, 0, 4, 1, 2] };
+        let tsp_func = RandomKeyTspFunc::new(TspFitness { distances: Distances::new(5, true) });
+        assert_eq!(tsp_func.decode(&random_key_encode_tsp(&tsp_perm)).vert_perm, tsp_perm.vert_perm);
+
+        let spec = Rc::new(GtspProblem {
+            vert_count: 6,
+            best_known: 0.0,
+            groups: vec![vec![0, 1], vec![2], vec![3, 4, 5]],
+            distances: Distances::new(6, true)
+        });
+        let gtsp_perm = GtspPermutation { spec: spec.clone(), perm: vec![
+            GroupVert { group: 2, vert: 2 }, GroupVert { group: 0, vert: 1 }, GroupVert { group: 1, vert: 0 }] };
+        let gtsp_func = RandomKeyGtspFunc::new(GtspFitness {}, spec);
+        let decoded = gtsp_func.decode(&random_key_encode_gtsp(&gtsp_perm));
+        for i in 0..3 {
+            assert_eq!(decoded.perm[i].group, gtsp_perm.perm[i].group);
+            assert_eq!(decoded.perm[i].vert, gtsp_perm.perm[i].vert);
+        }
+    }
+}
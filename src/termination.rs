@@ -1,5 +1,7 @@
 use crate::opt_traits::*;
 use crate::opt_data::*;
+use crate::bit_array::*;
+use crate::es::*;
 
 pub struct MaxIterTerminationCond {
     pub n_iters: usize
@@ -11,8 +13,26 @@ impl TerminationCond<NaiveBitVec> for MaxIterTerminationCond {
     }
 }
 
+impl TerminationCond<BitVec> for MaxIterTerminationCond {
+    fn eval(&self, iter: usize, _: f64) -> bool {
+        return iter >= self.n_iters;
+    }
+}
+
+impl TerminationCond<IntVec> for MaxIterTerminationCond {
+    fn eval(&self, iter: usize, _: f64) -> bool {
+        return iter >= self.n_iters;
+    }
+}
+
 impl TerminationCond<FloatVec> for MaxIterTerminationCond {
     fn eval(&self, iter: usize, _: f64) -> bool {
         return iter >= self.n_iters;
     }
-}
\ No newline at end of file
+}
+
+impl TerminationCond<EsFloatVec> for MaxIterTerminationCond {
+    fn eval(&self, iter: usize, _: f64) -> bool {
+        return iter >= self.n_iters;
+    }
+}
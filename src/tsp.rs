@@ -4,6 +4,7 @@ use crate::opt_traits::*;
 use crate::opt_data::*;
 use crate::crossover::*;
 use crate::termination::*;
+use crate::coord_distances::*;
 use rand::Rng;
 
 #[derive(Clone)]
@@ -32,6 +33,17 @@ impl VecOptData<usize> for TspPermutation {
     }
 }
 
+// Distances between vertices, get(v1, v2) is the length of the edge from v1 to v2
+pub trait DistanceMatrix {
+    fn get_vert_count(&self) -> usize;
+
+    fn get(&self, v1: usize, v2: usize) -> f64;
+
+    // true when get(v1, v2) == get(v2, v1) is guaranteed by the representation
+    fn is_symmetric(&self) -> bool;
+}
+
+// Symmetric distances, only the lower triangle is stored
 #[derive(Clone)]
 pub struct DistanceHalfMatrix {
     vert_count: usize,
@@ -50,14 +62,6 @@ impl DistanceHalfMatrix {
         DistanceHalfMatrix { vert_count: vert_count, distances: distances.clone() }
     }
 
-    pub fn get_vert_count(&self) -> usize {
-        self.vert_count
-    }
-
-    pub fn get(&self, v1: usize, v2: usize) -> f64 {
-        self.distances[self.get_index(v1, v2)]
-    }
-
     pub fn set(&mut self, v1: usize, v2: usize, dist: f64) {
         let index = self.get_index(v1, v2);
         self.distances[index] = dist;
@@ -77,15 +81,152 @@ impl DistanceHalfMatrix {
     }
 }
 
-pub struct TspFitness {
-    pub distances: DistanceHalfMatrix
+impl DistanceMatrix for DistanceHalfMatrix {
+    fn get_vert_count(&self) -> usize {
+        self.vert_count
+    }
+
+    fn get(&self, v1: usize, v2: usize) -> f64 {
+        self.distances[self.get_index(v1, v2)]
+    }
+
+    fn is_symmetric(&self) -> bool {
+        true
+    }
+}
+
+// Possibly asymmetric distances, row v1 holds the edges leaving v1
+#[derive(Clone)]
+pub struct DistanceFullMatrix {
+    vert_count: usize,
+    distances: Vec<f64>
+}
+
+impl DistanceFullMatrix {
+    pub fn new(vert_count: usize) -> Self {
+        DistanceFullMatrix { vert_count, distances: vec![0.0f64; vert_count * vert_count] }
+    }
+
+    pub fn from(vert_count: usize, distances: Vec<f64>) -> Self {
+        if vert_count * vert_count != distances.len() {
+            panic!("incorrect size of distances array");
+        }
+        DistanceFullMatrix { vert_count, distances }
+    }
+
+    pub fn set(&mut self, v1: usize, v2: usize, dist: f64) {
+        self.distances[v1 * self.vert_count + v2] = dist;
+    }
+
+    pub fn has_symmetric_values(&self) -> bool {
+        (0..self.vert_count).all(|v1| (0..v1).all(|v2| self.get(v1, v2) == self.get(v2, v1)))
+    }
+}
+
+impl DistanceMatrix for DistanceFullMatrix {
+    fn get_vert_count(&self) -> usize {
+        self.vert_count
+    }
+
+    fn get(&self, v1: usize, v2: usize) -> f64 {
+        self.distances[v1 * self.vert_count + v2]
+    }
+
+    fn is_symmetric(&self) -> bool {
+        false
+    }
+}
+
+// Distances used by the problems, symmetric ones keep the smaller half matrix,
+// large instances compute them from the coordinates
+#[derive(Clone)]
+pub enum Distances {
+    Symmetric(DistanceHalfMatrix),
+    Asymmetric(DistanceFullMatrix),
+    Coords(CoordDistances)
 }
 
-impl FitnessFunc<TspPermutation> for TspFitness {
+impl Distances {
+    pub fn new(vert_count: usize, symmetric: bool) -> Self {
+        if symmetric {
+            Distances::Symmetric(DistanceHalfMatrix::new(vert_count))
+        } else {
+            Distances::Asymmetric(DistanceFullMatrix::new(vert_count))
+        }
+    }
+
+    // keeps the full matrix only when it is really asymmetric
+    pub fn from_full_matrix(full: DistanceFullMatrix) -> Self {
+        if !full.has_symmetric_values() {
+            return Distances::Asymmetric(full);
+        }
+        let mut half = DistanceHalfMatrix::new(full.get_vert_count());
+        for v1 in 0..full.get_vert_count() {
+            for v2 in 0..=v1 {
+                half.set(v1, v2, full.get(v1, v2));
+            }
+        }
+        Distances::Symmetric(half)
+    }
+
+    pub fn set(&mut self, v1: usize, v2: usize, dist: f64) {
+        match self {
+            Distances::Symmetric(half) => half.set(v1, v2, dist),
+            Distances::Asymmetric(full) => full.set(v1, v2, dist),
+            Distances::Coords(_) => panic!("coordinate distances can't be set")
+        }
+    }
+}
+
+impl DistanceMatrix for Distances {
+    fn get_vert_count(&self) -> usize {
+        match self {
+            Distances::Symmetric(half) => half.get_vert_count(),
+            Distances::Asymmetric(full) => full.get_vert_count(),
+            Distances::Coords(coords) => coords.get_vert_count()
+        }
+    }
+
+    fn get(&self, v1: usize, v2: usize) -> f64 {
+        match self {
+            Distances::Symmetric(half) => half.get(v1, v2),
+            Distances::Asymmetric(full) => full.get(v1, v2),
+            Distances::Coords(coords) => coords.get(v1, v2)
+        }
+    }
+
+    fn is_symmetric(&self) -> bool {
+        !matches!(self, Distances::Asymmetric(_))
+    }
+}
+
+impl From<DistanceHalfMatrix> for Distances {
+    fn from(half: DistanceHalfMatrix) -> Self {
+        Distances::Symmetric(half)
+    }
+}
+
+impl From<DistanceFullMatrix> for Distances {
+    fn from(full: DistanceFullMatrix) -> Self {
+        Distances::Asymmetric(full)
+    }
+}
+
+impl From<CoordDistances> for Distances {
+    fn from(coords: CoordDistances) -> Self {
+        Distances::Coords(coords)
+    }
+}
+
+pub struct TspFitness<DistanceMatrixT: DistanceMatrix = Distances> {
+    pub distances: DistanceMatrixT
+}
+
+impl<DistanceMatrixT: DistanceMatrix> FitnessFunc<TspPermutation> for TspFitness<DistanceMatrixT> {
     fn eval(&self, data: &TspPermutation) -> f64 {
         let mut total_len = 0f64;
         for i in 0..data.dim() {
-            total_len += self.distances.get(data.vert_perm[(i + 1) % data.dim()], data.vert_perm[i]);
+            total_len += self.distances.get(data.vert_perm[i], data.vert_perm[(i + 1) % data.dim()]);
         }
         total_len
     }
@@ -136,24 +277,151 @@ impl InitFunc<TspPermutation> for InitTspPopulation {
     }
 }
 
-#[derive(Clone)]
-pub struct TspMovePerturbation {
+// Move of a permutation, positions are indices into the permutation
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PermMove {
+    // vertex on the position from is moved to the position to, the vertices between are shifted
+    Move { from: usize, to: usize },
+    Swap { pos1: usize, pos2: usize },
+    // reverses the positions from..=to cyclically, to may exceed the permutation length
+    Reverse { from: usize, to: usize }
 }
 
-pub fn tsp_move_perturbation<V: Copy>(perm: &mut Vec<V>) {
-    let move_from = rand::thread_rng().gen_range(0..perm.len());
-    let move_to = rand::thread_rng().gen_range(0..perm.len());
-    let vert_to_move = perm[move_from];
-    if move_to >= move_from {
-        for i in move_from..move_to {
-            perm[i] = perm[i + 1];
+impl PermMove {
+    pub fn random_move(len: usize) -> Self {
+        let from = rand::thread_rng().gen_range(0..len);
+        let to = rand::thread_rng().gen_range(0..len);
+        PermMove::Move { from, to }
+    }
+
+    pub fn random_swap(len: usize) -> Self {
+        let pos1 = rand::thread_rng().gen_range(0..len);
+        let pos2 = rand::thread_rng().gen_range(0..len);
+        PermMove::Swap { pos1, pos2 }
+    }
+
+    pub fn random_reverse(len: usize) -> Self {
+        let from = rand::thread_rng().gen_range(0..len);
+        let mut to = rand::thread_rng().gen_range(0..len);
+        if to < from {
+            to += len;
         }
-    } else {
-        for i in (move_to..move_from).rev() {
-            perm[i + 1] = perm[i];
+        PermMove::Reverse { from, to }
+    }
+}
+
+pub fn apply_perm_move<V: Copy>(perm: &mut [V], mv: &PermMove) {
+    match *mv {
+        PermMove::Move { from, to } => {
+            let vert_to_move = perm[from];
+            if to >= from {
+                perm.copy_within(from + 1..to + 1, from);
+            } else {
+                perm.copy_within(to..from, to + 1);
+            }
+            perm[to] = vert_to_move;
+        },
+        PermMove::Swap { pos1, pos2 } => perm.swap(pos1, pos2),
+        PermMove::Reverse { from, to } => {
+            let range_len = to - from + 1;
+            for offset in 0..(range_len / 2) {
+                perm.swap((from + offset) % perm.len(), (to - offset) % perm.len());
+            }
         }
     }
-    perm[move_to] = vert_to_move;
+}
+
+pub fn tour_len<V, DistFunT: Fn(&V, &V) -> f64>(perm: &[V], dist: &DistFunT) -> f64 {
+    (0..perm.len()).map(|i| dist(&perm[i], &perm[(i + 1) % perm.len()])).sum()
+}
+
+fn perm_move_delta_by_eval<V: Copy, DistFunT: Fn(&V, &V) -> f64>(perm: &[V], mv: &PermMove, dist: &DistFunT) -> f64 {
+    let mut moved = perm.to_vec();
+    apply_perm_move(&mut moved, mv);
+    tour_len(&moved, dist) - tour_len(perm, dist)
+}
+
+// Change of the tour length caused by the move in O(1), reversals of asymmetric tours take
+// O(segment length) because the direction of the inner edges changes
+pub fn perm_move_delta<V: Copy, DistFunT: Fn(&V, &V) -> f64>(perm: &[V], mv: &PermMove, symmetric: bool, dist: &DistFunT) -> f64 {
+    let n = perm.len();
+    if n < 4 {
+        return perm_move_delta_by_eval(perm, mv, dist);
+    }
+    let at = |i: usize| &perm[i % n];
+    match *mv {
+        PermMove::Move { from, to } => {
+            if from == to {
+                return 0.0;
+            }
+            let v = at(from);
+            let a = at(from + n - 1);
+            let b = at(from + 1);
+            // neighbours of the inserted vertex are taken from the permutation without it
+            let without = |i: usize| {
+                let i = i % (n - 1);
+                if i < from { &perm[i] } else { &perm[i + 1] }
+            };
+            let c = without(to + n - 2);
+            let e = without(to);
+            dist(a, b) - dist(a, v) - dist(v, b) + dist(c, v) + dist(v, e) - dist(c, e)
+        },
+        PermMove::Swap { pos1, pos2 } => {
+            if pos1 == pos2 {
+                return 0.0;
+            }
+            let new_at = |i: usize| {
+                let i = i % n;
+                if i == pos1 { &perm[pos2] } else if i == pos2 { &perm[pos1] } else { &perm[i] }
+            };
+            // starts of the changed edges
+            let mut edges = [pos1 + n - 1, pos1, pos2 + n - 1, pos2].map(|i| i % n);
+            edges.sort();
+            let mut delta = 0.0;
+            for k in 0..edges.len() {
+                if k > 0 && edges[k] == edges[k - 1] {
+                    continue;
+                }
+                delta += dist(new_at(edges[k]), new_at(edges[k] + 1)) - dist(at(edges[k]), at(edges[k] + 1));
+            }
+            delta
+        },
+        PermMove::Reverse { from, to } => {
+            let range_len = to - from + 1;
+            if range_len <= 1 || (range_len >= n && symmetric) {
+                return 0.0;
+            }
+            if range_len >= n {
+                return perm_move_delta_by_eval(perm, mv, dist);
+            }
+            let a = at(from + n - 1);
+            let b = at(from);
+            let c = at(to);
+            let e = at(to + 1);
+            let mut delta = dist(a, c) + dist(b, e) - dist(a, b) - dist(c, e);
+            if !symmetric {
+                for k in from..to {
+                    delta += dist(at(k + 1), at(k)) - dist(at(k), at(k + 1));
+                }
+            }
+            delta
+        }
+    }
+}
+
+impl<DistanceMatrixT: DistanceMatrix> MoveFitnessFunc<TspPermutation, PermMove> for TspFitness<DistanceMatrixT> {
+    fn move_delta(&self, data: &TspPermutation, mv: &PermMove) -> f64 {
+        perm_move_delta(&data.vert_perm, mv, self.distances.is_symmetric(), &|u: &usize, v: &usize| self.distances.get(*u, *v))
+    }
+}
+
+#[derive(Clone)]
+pub struct TspMovePerturbation {
+}
+
+pub fn tsp_move_perturbation<V: Copy>(perm: &mut Vec<V>) {
+    let mv = PermMove::random_move(perm.len());
+    apply_perm_move(perm, &mv);
 }
 
 impl PerturbeMutOp<TspPermutation> for TspMovePerturbation {
@@ -162,16 +430,21 @@ impl PerturbeMutOp<TspPermutation> for TspMovePerturbation {
     }
 }
 
+impl MovePerturbeMutOp<TspPermutation, PermMove> for TspMovePerturbation {
+    fn eval_moves(&self, data: &mut TspPermutation, on_move: &mut dyn FnMut(&TspPermutation, &PermMove)) {
+        let mv = PermMove::random_move(data.vert_perm.len());
+        on_move(data, &mv);
+        apply_perm_move(&mut data.vert_perm, &mv);
+    }
+}
+
 #[derive(Clone)]
 pub struct TspSwapPerturbation {
 }
 
 pub fn tsp_swap_perturbation<V: Copy>(perm: &mut Vec<V>) {
-    let pos1 = rand::thread_rng().gen_range(0..perm.len());
-    let pos2 = rand::thread_rng().gen_range(0..perm.len());
-    let temp = perm[pos1];
-    perm[pos1] = perm[pos2];
-    perm[pos2] = temp;
+    let mv = PermMove::random_swap(perm.len());
+    apply_perm_move(perm, &mv);
 }
 
 impl PerturbeMutOp<TspPermutation> for TspSwapPerturbation {
@@ -180,24 +453,21 @@ impl PerturbeMutOp<TspPermutation> for TspSwapPerturbation {
     }
 }
 
+impl MovePerturbeMutOp<TspPermutation, PermMove> for TspSwapPerturbation {
+    fn eval_moves(&self, data: &mut TspPermutation, on_move: &mut dyn FnMut(&TspPermutation, &PermMove)) {
+        let mv = PermMove::random_swap(data.vert_perm.len());
+        on_move(data, &mv);
+        apply_perm_move(&mut data.vert_perm, &mv);
+    }
+}
+
 #[derive(Clone)]
 pub struct TspReversePerturbation {
 }
 
 pub fn tsp_reverse_perturbation<V: Copy>(perm: &mut Vec<V>) {
-    let from = rand::thread_rng().gen_range(0..perm.len());
-    let mut to = rand::thread_rng().gen_range(0..perm.len());
-    if to < from {
-        to += perm.len();
-    }
-    let range_len = to - from;
-    for offset in 0..(range_len / 2) {
-        let pos1 = (from + offset) % perm.len();
-        let pos2 = (to - offset) % perm.len();
-        let temp = perm[pos1];
-        perm[pos1] = perm[pos2];
-        perm[pos2] = temp;
-    }
+    let mv = PermMove::random_reverse(perm.len());
+    apply_perm_move(perm, &mv);
 }
 
 impl PerturbeMutOp<TspPermutation> for TspReversePerturbation {
@@ -206,14 +476,33 @@ impl PerturbeMutOp<TspPermutation> for TspReversePerturbation {
     }
 }
 
+impl MovePerturbeMutOp<TspPermutation, PermMove> for TspReversePerturbation {
+    fn eval_moves(&self, data: &mut TspPermutation, on_move: &mut dyn FnMut(&TspPermutation, &PermMove)) {
+        let mv = PermMove::random_reverse(data.vert_perm.len());
+        on_move(data, &mv);
+        apply_perm_move(&mut data.vert_perm, &mv);
+    }
+}
+
 pub trait SameVertex {
     fn is_same(&self, other: &Self) -> bool;
+    // index in 0..permutation length, same vertices have the same id
+    fn id(&self) -> usize;
 }
 
 impl SameVertex for usize {
     fn is_same(&self, other: &Self) -> bool {
         self == other
     }
+    fn id(&self) -> usize {
+        *self
+    }
+}
+
+impl<MultiCrossoverFunT: MultiCrossoverFun<usize>> Crossover<TspPermutation> for MultiCrossover<MultiCrossoverFunT> {
+    fn crossover(&self, population: &Vec<TspPermutation>, parents_indices: &Vec<usize>, offsprings: &mut Vec<TspPermutation>) {
+        multi_crossover_vec_data(population, parents_indices, offsprings, &self.crossover_fun, self.crossover_prob);
+    }
 }
 
 pub fn tsp_cycle_crossover<V: Copy + SameVertex>(parents: [&Vec<V>; 2], offsprings: [&mut Vec<V>; 2]) {
@@ -310,8 +599,446 @@ impl Crossover<TspPermutation> for TspOrderCrossover {
     }
 }
 
+fn positions_by_id<V: Copy + SameVertex>(perm: &Vec<V>) -> Vec<usize> {
+    let mut positions = vec![0usize; perm.len()];
+    for i in 0..perm.len() {
+        positions[perm[i].id()] = i;
+    }
+    positions
+}
+
+pub fn tsp_pmx_crossover<V: Copy + SameVertex>(parents: [&Vec<V>; 2], offsprings: [&mut Vec<V>; 2]) {
+    let len = parents[0].len();
+    let from = rand::thread_rng().gen_range(0..len);
+    let to = rand::thread_rng().gen_range(from..len) + 1;
+    for o in 0..2 {
+        let p1 = parents[o];
+        let p2 = parents[1 - o];
+        let p1_positions = positions_by_id(p1);
+        for i in 0..len {
+            if i >= from && i < to {
+                offsprings[o].push(p1[i]);
+                continue;
+            }
+            // follow the mapping given by the segment until the vertex is not inside of it
+            let mut candidate = p2[i];
+            let mut pos = p1_positions[candidate.id()];
+            while pos >= from && pos < to {
+                candidate = p2[pos];
+                pos = p1_positions[candidate.id()];
+            }
+            offsprings[o].push(candidate);
+        }
+    }
+}
+
+pub struct TspPmxCrossover {
+}
+
+impl<V: Copy + SameVertex> CrossoverFun<V> for TspPmxCrossover {
+    fn crossover_fun(&self, parents: [&Vec<V>; 2], offsprings: [&mut Vec<V>; 2]) {
+        tsp_pmx_crossover(parents, offsprings);
+    }
+}
+
+impl Crossover<TspPermutation> for TspPmxCrossover {
+    fn crossover(&self, population: &Vec<TspPermutation>, parents_indices: &Vec<usize>, offsprings: &mut Vec<TspPermutation>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
+pub fn tsp_position_crossover<V: Copy + SameVertex>(parents: [&Vec<V>; 2], offsprings: [&mut Vec<V>; 2]) {
+    let len = parents[0].len();
+    let keep: Vec<bool> = (0..len).map(|_| rand::random::<bool>()).collect();
+    for o in 0..2 {
+        let p1 = parents[o];
+        let p2 = parents[1 - o];
+        let mut used = vec![false; len];
+        for i in 0..len {
+            if keep[i] {
+                used[p1[i].id()] = true;
+            }
+        }
+        let mut p2_index = 0;
+        for i in 0..len {
+            if keep[i] {
+                offsprings[o].push(p1[i]);
+                continue;
+            }
+            while used[p2[p2_index].id()] {
+                p2_index += 1;
+            }
+            offsprings[o].push(p2[p2_index]);
+            p2_index += 1;
+        }
+    }
+}
+
+pub struct TspPositionCrossover {
+}
+
+impl<V: Copy + SameVertex> CrossoverFun<V> for TspPositionCrossover {
+    fn crossover_fun(&self, parents: [&Vec<V>; 2], offsprings: [&mut Vec<V>; 2]) {
+        tsp_position_crossover(parents, offsprings);
+    }
+}
+
+impl Crossover<TspPermutation> for TspPositionCrossover {
+    fn crossover(&self, population: &Vec<TspPermutation>, parents_indices: &Vec<usize>, offsprings: &mut Vec<TspPermutation>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
+// undirected edges of the tour as neighbour lists indexed by vertex id
+fn tour_adjacency<V: Copy + SameVertex>(perm: &Vec<V>) -> Vec<[usize; 2]> {
+    let len = perm.len();
+    let mut adjacency = vec![[0usize; 2]; len];
+    for i in 0..len {
+        adjacency[perm[i].id()] = [perm[(i + len - 1) % len].id(), perm[(i + 1) % len].id()];
+    }
+    adjacency
+}
+
+pub fn tsp_edge_recombination_crossover<V: Copy + SameVertex>(parents: [&Vec<V>; 2], offsprings: [&mut Vec<V>; 2]) {
+    let len = parents[0].len();
+    let adjacencies = [tour_adjacency(parents[0]), tour_adjacency(parents[1])];
+    for o in 0..2 {
+        let mut values = vec![parents[o][0]; len];
+        for v in parents[o] {
+            values[v.id()] = *v;
+        }
+        // union of the parents edges, edges shared by both parents are listed twice
+        let mut edge_table = vec![Vec::<usize>::with_capacity(4); len];
+        for id in 0..len {
+            for adjacency in &adjacencies {
+                edge_table[id].extend_from_slice(&adjacency[id]);
+            }
+        }
+        let mut visited = vec![false; len];
+        let mut unvisited: Vec<usize> = (0..len).collect();
+        let mut current = parents[o][0].id();
+        loop {
+            offsprings[o].push(values[current]);
+            visited[current] = true;
+            if let Some(pos) = unvisited.iter().position(|id| *id == current) {
+                unvisited.swap_remove(pos);
+            }
+            if unvisited.is_empty() {
+                break;
+            }
+            for id in edge_table[current].clone() {
+                edge_table[id].retain(|n| *n != current);
+            }
+            let mut candidates: Vec<usize> = edge_table[current].iter().copied().filter(|n| !visited[*n]).collect();
+            if candidates.is_empty() {
+                current = unvisited[rand::thread_rng().gen_range(0..unvisited.len())];
+                continue;
+            }
+            // prefer shared edges, then neighbours with the fewest remaining edges
+            let mut shared: Vec<usize> = candidates.iter().copied()
+                .filter(|n| edge_table[current].iter().filter(|m| *m == n).count() > 1)
+                .collect();
+            if !shared.is_empty() {
+                candidates = std::mem::take(&mut shared);
+            }
+            candidates.sort();
+            candidates.dedup();
+            let min_edges = candidates.iter().map(|n| edge_table[*n].len()).min().unwrap();
+            candidates.retain(|n| edge_table[*n].len() == min_edges);
+            current = candidates[rand::thread_rng().gen_range(0..candidates.len())];
+        }
+    }
+}
+
+pub struct TspEdgeRecombinationCrossover {
+}
+
+impl<V: Copy + SameVertex> CrossoverFun<V> for TspEdgeRecombinationCrossover {
+    fn crossover_fun(&self, parents: [&Vec<V>; 2], offsprings: [&mut Vec<V>; 2]) {
+        tsp_edge_recombination_crossover(parents, offsprings);
+    }
+}
+
+impl Crossover<TspPermutation> for TspEdgeRecombinationCrossover {
+    fn crossover(&self, population: &Vec<TspPermutation>, parents_indices: &Vec<usize>, offsprings: &mut Vec<TspPermutation>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EaxStrategy {
+    // applies a single random AB-cycle, keeps offsprings close to the first parent
+    Single,
+    // applies each AB-cycle with probability 0.5
+    Rand
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum EaxEdgeType {
+    A,
+    B
+}
+
+// decomposes the symmetric difference of the parents edges into cycles alternating between their edges
+fn eax_ab_cycles(adjacency_a: &Vec<[usize; 2]>, adjacency_b: &Vec<[usize; 2]>) -> Vec<Vec<(usize, usize, EaxEdgeType)>> {
+    let len = adjacency_a.len();
+    let mut edges = [vec![Vec::<usize>::with_capacity(2); len], vec![Vec::<usize>::with_capacity(2); len]];
+    for v in 0..len {
+        for n in adjacency_a[v] {
+            if !adjacency_b[v].contains(&n) {
+                edges[0][v].push(n);
+            }
+        }
+        for n in adjacency_b[v] {
+            if !adjacency_a[v].contains(&n) {
+                edges[1][v].push(n);
+            }
+        }
+    }
+    let mut cycles = Vec::<Vec<(usize, usize, EaxEdgeType)>>::new();
+    let mut positions = vec![Vec::<usize>::new(); len];
+    while let Some(start) = (0..len).find(|v| !edges[0][*v].is_empty()) {
+        let mut path = vec![start];
+        let mut path_edges = Vec::<(usize, usize, EaxEdgeType)>::new();
+        positions[start].push(0);
+        while !path.is_empty() {
+            let current = *path.last().unwrap();
+            let edge_type = if path_edges.len() % 2 == 0 { EaxEdgeType::A } else { EaxEdgeType::B };
+            let t = edge_type as usize;
+            if edges[t][current].is_empty() {
+                // cannot happen for valid tours, every vertex has the same number of A and B edges
+                for v in &path {
+                    positions[*v].clear();
+                }
+                break;
+            }
+            let next_index = rand::thread_rng().gen_range(0..edges[t][current].len());
+            let next = edges[t][current].swap_remove(next_index);
+            let back_index = edges[t][next].iter().position(|n| *n == current).unwrap();
+            edges[t][next].swap_remove(back_index);
+            path_edges.push((current, next, edge_type));
+            path.push(next);
+            let next_pos = path.len() - 1;
+            // cycle closes at an earlier occurrence whose outgoing edge has the other type
+            let closing = positions[next].iter().rev().copied().find(|k| (next_pos - k) % 2 == 0);
+            match closing {
+                Some(k) => {
+                    cycles.push(path_edges.split_off(k));
+                    for v in &path[(k + 1)..] {
+                        positions[*v].pop();
+                    }
+                    path.truncate(k + 1);
+                    if path_edges.is_empty() {
+                        positions[path[0]].clear();
+                        path.clear();
+                    }
+                },
+                None => positions[next].push(next_pos)
+            }
+        }
+    }
+    cycles
+}
+
+fn eax_subtours(adjacency: &Vec<Vec<usize>>) -> Vec<usize> {
+    let len = adjacency.len();
+    let mut labels = vec![usize::MAX; len];
+    let mut label = 0;
+    for start in 0..len {
+        if labels[start] != usize::MAX {
+            continue;
+        }
+        let mut prev = start;
+        let mut current = start;
+        loop {
+            labels[current] = label;
+            let next = if adjacency[current][0] != prev || adjacency[current][0] == adjacency[current][1] {
+                adjacency[current][0]
+            } else {
+                adjacency[current][1]
+            };
+            prev = current;
+            current = next;
+            if current == start || labels[current] == label {
+                break;
+            }
+        }
+        label += 1;
+    }
+    labels
+}
+
+fn replace_neighbour(adjacency: &mut Vec<Vec<usize>>, v: usize, old: usize, new: usize) {
+    let index = adjacency[v].iter().position(|n| *n == old).unwrap();
+    adjacency[v][index] = new;
+}
+
+// EAX with greedy 2-opt merging of the subtours, offspring o is built from parent o by applying
+// AB-cycles with the edges of the other parent. Edges are undirected, with asymmetric distances
+// the offsprings stay valid but the merging costs are only approximate
+pub fn tsp_eax_crossover<V: Copy + SameVertex, DistFunT: Fn(&V, &V) -> f64>(parents: [&Vec<V>; 2], offsprings: [&mut Vec<V>; 2], strategy: EaxStrategy, dist: &DistFunT) {
+    let len = parents[0].len();
+    let adjacencies = [tour_adjacency(parents[0]), tour_adjacency(parents[1])];
+    for o in 0..2 {
+        let mut values = vec![parents[o][0]; len];
+        for v in parents[o] {
+            values[v.id()] = *v;
+        }
+        let d = |u: usize, v: usize| dist(&values[u], &values[v]);
+        let cycles = eax_ab_cycles(&adjacencies[o], &adjacencies[1 - o]);
+        let mut adjacency: Vec<Vec<usize>> = adjacencies[o].iter().map(|n| n.to_vec()).collect();
+        if !cycles.is_empty() {
+            let single = rand::thread_rng().gen_range(0..cycles.len());
+            for c in 0..cycles.len() {
+                let apply = match strategy {
+                    EaxStrategy::Single => c == single,
+                    EaxStrategy::Rand => rand::random::<bool>()
+                };
+                if !apply {
+                    continue;
+                }
+                for (u, v, edge_type) in &cycles[c] {
+                    if *edge_type == EaxEdgeType::A {
+                        let index = adjacency[*u].iter().position(|n| n == v).unwrap();
+                        adjacency[*u].swap_remove(index);
+                        let index = adjacency[*v].iter().position(|n| n == u).unwrap();
+                        adjacency[*v].swap_remove(index);
+                    }
+                }
+                for (u, v, edge_type) in &cycles[c] {
+                    if *edge_type == EaxEdgeType::B {
+                        adjacency[*u].push(*v);
+                        adjacency[*v].push(*u);
+                    }
+                }
+            }
+        }
+        // merge the smallest subtour into the rest until a single tour remains
+        loop {
+            let labels = eax_subtours(&adjacency);
+            let subtours_count = labels.iter().max().unwrap() + 1;
+            if subtours_count <= 1 {
+                break;
+            }
+            let mut sizes = vec![0usize; subtours_count];
+            for label in &labels {
+                sizes[*label] += 1;
+            }
+            let smallest = (0..subtours_count).min_by_key(|l| sizes[*l]).unwrap();
+            let mut best = (f64::INFINITY, 0, 0, 0, 0);
+            for u in (0..len).filter(|u| labels[*u] == smallest) {
+                for u_next in adjacency[u].clone() {
+                    for v in (0..len).filter(|v| labels[*v] != smallest) {
+                        for v_next in adjacency[v].clone() {
+                            let removed = d(u, u_next) + d(v, v_next);
+                            let cost1 = d(u, v) + d(u_next, v_next) - removed;
+                            if cost1 < best.0 {
+                                best = (cost1, u, u_next, v, v_next);
+                            }
+                            let cost2 = d(u, v_next) + d(u_next, v) - removed;
+                            if cost2 < best.0 {
+                                best = (cost2, u, u_next, v_next, v);
+                            }
+                        }
+                    }
+                }
+            }
+            let (_, u, u_next, v, v_next) = best;
+            replace_neighbour(&mut adjacency, u, u_next, v);
+            replace_neighbour(&mut adjacency, v, v_next, u);
+            replace_neighbour(&mut adjacency, u_next, u, v_next);
+            replace_neighbour(&mut adjacency, v_next, v, u_next);
+        }
+        let mut prev = parents[o][0].id();
+        let mut current = adjacency[prev][0];
+        offsprings[o].push(values[prev]);
+        while offsprings[o].len() < len {
+            offsprings[o].push(values[current]);
+            let next = if adjacency[current][0] != prev { adjacency[current][0] } else { adjacency[current][1] };
+            prev = current;
+            current = next;
+        }
+    }
+}
+
+pub struct TspEaxCrossover<DistanceMatrixT: DistanceMatrix = Distances> {
+    pub distances: DistanceMatrixT,
+    pub strategy: EaxStrategy
+}
+
+impl<DistanceMatrixT: DistanceMatrix> CrossoverFun<usize> for TspEaxCrossover<DistanceMatrixT> {
+    fn crossover_fun(&self, parents: [&Vec<usize>; 2], offsprings: [&mut Vec<usize>; 2]) {
+        tsp_eax_crossover(parents, offsprings, self.strategy, &|u: &usize, v: &usize| self.distances.get(*u, *v));
+    }
+}
+
+impl<DistanceMatrixT: DistanceMatrix> Crossover<TspPermutation> for TspEaxCrossover<DistanceMatrixT> {
+    fn crossover(&self, population: &Vec<TspPermutation>, parents_indices: &Vec<usize>, offsprings: &mut Vec<TspPermutation>) {
+        crossover_vec_data(population, parents_indices, offsprings, self);
+    }
+}
+
 impl TerminationCond<TspPermutation> for MaxIterTerminationCond {
     fn eval(&self, iter: usize, _: f64) -> bool {
         return iter >= self.n_iters;
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn is_permutation(perm: &Vec<usize>, len: usize) -> bool {
+        let mut sorted = perm.clone();
+        sorted.sort();
+        sorted == (0..len).collect::<Vec<usize>>()
+    }
+
+    #[test]
+    fn test_move_delta_matches_eval() {
+        let n = 12;
+        for symmetric in [true, false] {
+            let mut distances = Distances::new(n, symmetric);
+            for v1 in 0..n {
+                for v2 in 0..n {
+                    distances.set(v1, v2, if v1 == v2 { 0.0 } else { rand::random::<f64>() });
+                }
+            }
+            let fitness = TspFitness { distances };
+            let mut perm = TspPermutation { vert_perm: (0..n).collect() };
+            for _ in 0..1000 {
+                let mv = match rand::thread_rng().gen_range(0..3) {
+                    0 => PermMove::random_move(n),
+                    1 => PermMove::random_swap(n),
+                    _ => PermMove::random_reverse(n)
+                };
+                let before = fitness.eval(&perm);
+                let delta = fitness.move_delta(&perm, &mv);
+                apply_perm_move(&mut perm.vert_perm, &mv);
+                assert!(is_permutation(&perm.vert_perm, n));
+                assert!((fitness.eval(&perm) - before - delta).abs() < 1.0e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_crossovers_keep_permutation() {
+        let len = 20;
+        let parent1: Vec<usize> = (0..len).collect();
+        let parent2: Vec<usize> = (0..len).map(|i| (i * 7) % len).rev().collect();
+        let dist = |u: &usize, v: &usize| (*u as f64 - *v as f64).abs();
+        for _ in 0..50 {
+            let mut offsprings = [Vec::<usize>::new(), Vec::<usize>::new(), Vec::<usize>::new(), Vec::<usize>::new(),
+                Vec::<usize>::new(), Vec::<usize>::new(), Vec::<usize>::new(), Vec::<usize>::new()];
+            let [o1, o2, o3, o4, o5, o6, o7, o8] = &mut offsprings;
+            tsp_pmx_crossover([&parent1, &parent2], [o1, o2]);
+            tsp_position_crossover([&parent1, &parent2], [o3, o4]);
+            tsp_edge_recombination_crossover([&parent1, &parent2], [o5, o6]);
+            tsp_eax_crossover([&parent1, &parent2], [o7, o8], EaxStrategy::Rand, &dist);
+            for offspring in &offsprings {
+                assert!(is_permutation(offspring, len));
+            }
+        }
+    }
+}
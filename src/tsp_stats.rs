@@ -5,9 +5,10 @@ pub fn create_comparison_graphs(num_repetitions: usize, num_iters: usize, popula
     let input_files = ["att48", "berlin52", "eil76"];
     let method_names = vec!["loc_move", "loc_swap", "loc_rev", "evo_cycle", "evo_order"];
     for input_file in input_files {
-        let vert_positions = load_vert_positions(format!("data/tsp/{}.tsp", input_file).as_str());
+        let problem = load_tsplib(format!("data/tsp/{}.tsp", input_file).as_str());
+        let vert_positions = problem.positions.clone();
         let opt_vert_permutation = load_opt_permutation(format!("data/tsp/{}.opt.tour", input_file).as_str());
-        let vert_distances = vert_positions_to_distances(&vert_positions);
+        let vert_distances = problem.distances();
         let mut fitness = TspFitness { distances: vert_distances };
         let opt_value = fitness.eval(&opt_vert_permutation);
         let vert_count = vert_positions.len();
@@ -70,6 +71,7 @@ pub fn create_comparison_graphs(num_repetitions: usize, num_iters: usize, popula
             }
         }
 
+        print_avg_stats_gap(input_file, &method_names, &avg_stats, num_repetitions, tsp_lower_bound(&fitness.distances, LOWER_BOUND_MAX_ITERS));
         for s in 0..avg_stats.len() {
             for i in 0..num_iters {
                 avg_stats[s].fitness[i] /= num_repetitions as f64;
@@ -86,9 +88,10 @@ pub fn create_comparison_graphs(num_repetitions: usize, num_iters: usize, popula
 pub fn create_vizualization_graphs(num_iters: usize, population_size: usize)
 {
     let input_file  = "berlin52";
-    let vert_positions = load_vert_positions(format!("data/tsp/{}.tsp", input_file).as_str());
+    let problem = load_tsplib(format!("data/tsp/{}.tsp", input_file).as_str());
+    let vert_positions = problem.positions.clone();
     let opt_vert_permutation = load_opt_permutation(format!("data/tsp/{}.opt.tour", input_file).as_str());
-    let vert_distances = vert_positions_to_distances(&vert_positions);
+    let vert_distances = problem.distances();
     let mut fitness = TspFitness { distances: vert_distances };
     let _opt_value = fitness.eval(&opt_vert_permutation);
     let vert_count = vert_positions.len();
@@ -118,3 +121,57 @@ pub fn create_vizualization_graphs(num_iters: usize, population_size: usize)
     plot_tsp_viz(&vert_positions, &opt_vert_permutation, "out/tsp/opt_viz.svg", "berlin52 optimum").unwrap();
     plot_tsp_viz(&vert_positions, &solution.value, format!("out/tsp/iter{}_viz.svg", num_iters).as_str(), format!("berlin52 iter{}", num_iters).as_str()).unwrap();
 }
+
+fn add_crossover_stats<CrossoverT: Crossover<TspPermutation>>(
+    avg_stats: &mut BSFSingleObjStatistics,
+    fitness: &mut TspFitness,
+    init_population: &InitTspPopulation,
+    crossover: &CrossoverT,
+    num_iters: usize,
+    population_size: usize)
+{
+    let termination_cond = MaxIterTerminationCond { n_iters: num_iters };
+    let selection = TournamentSelection { select_count: population_size / 2, rounds_count: 8 };
+    let replacement_strategy = TruncationReplacementStrategy {};
+    let (_, stats) : (BSFSingleObjSolution<TspPermutation>, BSFSingleObjStatistics) = evolutionary_search(
+        fitness,
+        init_population.clone(),
+        &selection,
+        crossover,
+        TspReversePerturbation {},
+        &replacement_strategy,
+        &termination_cond,
+        false);
+    for i in 0..num_iters {
+        avg_stats.fitness[i] += stats.fitness[i];
+    }
+}
+
+pub fn create_crossover_comparison_graphs(num_repetitions: usize, num_iters: usize, population_size: usize)
+{
+    let input_file = "eil76";
+    let method_names = vec!["cycle", "order", "pmx", "position", "erx", "eax_single", "eax_rand"];
+    let problem = load_tsplib(format!("data/tsp/{}.tsp", input_file).as_str());
+    let vert_positions = problem.positions.clone();
+    let opt_vert_permutation = load_opt_permutation(format!("data/tsp/{}.opt.tour", input_file).as_str());
+    let vert_distances = problem.distances();
+    let mut fitness = TspFitness { distances: vert_distances.clone() };
+    let opt_value = fitness.eval(&opt_vert_permutation);
+    let init_population = InitTspPopulation { size: population_size, vert_count: vert_positions.len() };
+    let eax_single = TspEaxCrossover { distances: vert_distances.clone(), strategy: EaxStrategy::Single };
+    let eax_rand = TspEaxCrossover { distances: vert_distances, strategy: EaxStrategy::Rand };
+
+    let mut avg_stats = vec![BSFSingleObjStatistics { fitness: vec![0.0f64; num_iters]}; method_names.len()];
+    for _rep in 0..num_repetitions {
+        add_crossover_stats(&mut avg_stats[0], &mut fitness, &init_population, &TspCycleCrossover {}, num_iters, population_size);
+        add_crossover_stats(&mut avg_stats[1], &mut fitness, &init_population, &TspOrderCrossover {}, num_iters, population_size);
+        add_crossover_stats(&mut avg_stats[2], &mut fitness, &init_population, &TspPmxCrossover {}, num_iters, population_size);
+        add_crossover_stats(&mut avg_stats[3], &mut fitness, &init_population, &TspPositionCrossover {}, num_iters, population_size);
+        add_crossover_stats(&mut avg_stats[4], &mut fitness, &init_population, &TspEdgeRecombinationCrossover {}, num_iters, population_size);
+        add_crossover_stats(&mut avg_stats[5], &mut fitness, &init_population, &eax_single, num_iters, population_size);
+        add_crossover_stats(&mut avg_stats[6], &mut fitness, &init_population, &eax_rand, num_iters, population_size);
+    }
+    print_avg_stats_gap(input_file, &method_names, &avg_stats, num_repetitions, tsp_lower_bound(&fitness.distances, LOWER_BOUND_MAX_ITERS));
+    let log_opt_value = process_avg_stats(&mut avg_stats, opt_value, num_iters, num_repetitions);
+    plot_multiple(&avg_stats, &method_names, &TAB_COLORS, format!("out/tsp/{}_crossovers.svg", input_file).as_str(), input_file, log_opt_value, "Log avg. fitness", true, true).unwrap();
+}
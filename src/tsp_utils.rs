@@ -1,43 +1,265 @@
 use crate::tsp::*;
+use crate::opt_data::*;
+use crate::coord_distances::*;
 
-use std::fs::File;
-use std::io::BufReader;
-use std::io::BufRead;
+use std::fs::{read_to_string, File};
+use std::io::Write;
 
-pub fn load_opt_permutation(file_path: &str) -> TspPermutation {
-    let file = File::open(file_path).expect("file wasn't found.");
-    let reader = BufReader::new(file);
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TsplibEdgeWeightType {
+    // plain euclidean distance without rounding, used for the bare "x y" files
+    Euclidean,
+    Euc2d,
+    Ceil2d,
+    Att,
+    Geo,
+    Explicit
+}
 
-    let vert_perm: Vec<usize> = reader
-        .lines()
-        .map(|line| line.unwrap().parse::<usize>().unwrap() - 1)
-        .collect();
-    TspPermutation { vert_perm: vert_perm }
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TsplibEdgeWeightFormat {
+    FullMatrix,
+    UpperRow,
+    LowerRow,
+    UpperDiagRow,
+    LowerDiagRow
 }
 
-pub fn load_vert_positions(file_path: &str) -> Vec<[f64; 2]> {
-    let file = File::open(file_path).expect("file wasn't found.");
-    let reader = BufReader::new(file);
-
-    let positions: Vec<[f64; 2]> = reader
-        .lines()
-        .map(|line| {
-            let line_nums : Vec<f64> = line.unwrap().split_whitespace()
-                .map(|num_str| num_str.parse::<f64>().unwrap())
-                .collect();
+#[derive(Clone)]
+pub struct TsplibProblem {
+    pub name: String,
+    pub problem_type: String,
+    pub dimension: usize,
+    pub edge_weight_type: TsplibEdgeWeightType,
+    pub edge_weight_format: Option<TsplibEdgeWeightFormat>,
+    // empty for explicit weights without coordinates
+    pub positions: Vec<[f64; 2]>,
+    // values of EDGE_WEIGHT_SECTION in the file order
+    pub edge_weights: Vec<f64>
+}
+
+fn parse_edge_weight_type(value: &str) -> TsplibEdgeWeightType {
+    match value {
+        "EUC_2D" => TsplibEdgeWeightType::Euc2d,
+        "CEIL_2D" => TsplibEdgeWeightType::Ceil2d,
+        "ATT" => TsplibEdgeWeightType::Att,
+        "GEO" => TsplibEdgeWeightType::Geo,
+        "EXPLICIT" => TsplibEdgeWeightType::Explicit,
+        _ => panic!("unsupported EDGE_WEIGHT_TYPE {}", value)
+    }
+}
+
+fn parse_edge_weight_format(value: &str) -> TsplibEdgeWeightFormat {
+    match value {
+        "FULL_MATRIX" => TsplibEdgeWeightFormat::FullMatrix,
+        "UPPER_ROW" => TsplibEdgeWeightFormat::UpperRow,
+        "LOWER_ROW" => TsplibEdgeWeightFormat::LowerRow,
+        "UPPER_DIAG_ROW" => TsplibEdgeWeightFormat::UpperDiagRow,
+        "LOWER_DIAG_ROW" => TsplibEdgeWeightFormat::LowerDiagRow,
+        _ => panic!("unsupported EDGE_WEIGHT_FORMAT {}", value)
+    }
+}
+
+fn starts_with_number(line: &str) -> bool {
+    line.split_whitespace().next().is_some_and(|token| token.parse::<f64>().is_ok())
+}
+
+// Parses a TSPLIB problem, files without a header are read as bare "x y" lines
+pub fn parse_tsplib(content: &str) -> TsplibProblem {
+    let mut problem = TsplibProblem {
+        name: String::new(),
+        problem_type: String::from("TSP"),
+        dimension: 0,
+        edge_weight_type: TsplibEdgeWeightType::Euclidean,
+        edge_weight_format: None,
+        positions: Vec::new(),
+        edge_weights: Vec::new()
+    };
+    let mut lines = content.lines().map(|line| line.trim()).filter(|line| !line.is_empty()).peekable();
+    if lines.peek().is_some_and(|line| starts_with_number(line)) {
+        for line in lines {
+            let line_nums: Vec<f64> = line.split_whitespace().map(|num_str| num_str.parse::<f64>().unwrap()).collect();
             if line_nums.len() != 2 {
                 panic!("incorect format of vertex positons");
             }
-            [line_nums[0], line_nums[1]]
-        })
+            problem.positions.push([line_nums[0], line_nums[1]]);
+        }
+        problem.dimension = problem.positions.len();
+        return problem;
+    }
+    while let Some(line) = lines.next() {
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => (line, "")
+        };
+        match key {
+            "NAME" => problem.name = value.to_string(),
+            "TYPE" => problem.problem_type = value.to_string(),
+            "DIMENSION" => problem.dimension = value.parse().expect("invalid DIMENSION"),
+            "EDGE_WEIGHT_TYPE" => problem.edge_weight_type = parse_edge_weight_type(value),
+            "EDGE_WEIGHT_FORMAT" => problem.edge_weight_format = Some(parse_edge_weight_format(value)),
+            "NODE_COORD_SECTION" => {
+                for _ in 0..problem.dimension {
+                    let line_nums: Vec<f64> = lines.next().expect("missing node coordinates").split_whitespace()
+                        .map(|num_str| num_str.parse::<f64>().unwrap())
+                        .collect();
+                    if line_nums.len() != 3 {
+                        panic!("incorect format of node coordinates");
+                    }
+                    problem.positions.push([line_nums[1], line_nums[2]]);
+                }
+            },
+            "EDGE_WEIGHT_SECTION" => {
+                while lines.peek().is_some_and(|line| starts_with_number(line)) {
+                    problem.edge_weights.extend(lines.next().unwrap().split_whitespace().map(|num_str| num_str.parse::<f64>().unwrap()));
+                }
+            },
+            "DISPLAY_DATA_SECTION" => {
+                while lines.peek().is_some_and(|line| starts_with_number(line)) {
+                    lines.next();
+                }
+            },
+            "EOF" => break,
+            _ => {}
+        }
+    }
+    problem
+}
+
+pub fn load_tsplib(file_path: &str) -> TsplibProblem {
+    parse_tsplib(&read_to_string(file_path).expect("file wasn't found."))
+}
+
+// nearest integer as defined by TSPLIB
+fn nint(value: f64) -> f64 {
+    (value + 0.5).floor()
+}
+
+// TSPLIB uses the truncated value
+#[allow(clippy::approx_constant)]
+const TSPLIB_PI: f64 = 3.141592;
+
+// latitude or longitude in radians, the coordinate is DDD.MM in degrees and minutes
+fn geo_radians(coord: f64) -> f64 {
+    let deg = coord.trunc();
+    let min = coord - deg;
+    TSPLIB_PI * (deg + 5.0 * min / 3.0) / 180.0
+}
+
+pub fn tsplib_distance(pos1: &[f64; 2], pos2: &[f64; 2], edge_weight_type: TsplibEdgeWeightType) -> f64 {
+    let dx = pos1[0] - pos2[0];
+    let dy = pos1[1] - pos2[1];
+    match edge_weight_type {
+        TsplibEdgeWeightType::Euclidean => (dx * dx + dy * dy).sqrt(),
+        TsplibEdgeWeightType::Euc2d => nint((dx * dx + dy * dy).sqrt()),
+        TsplibEdgeWeightType::Ceil2d => (dx * dx + dy * dy).sqrt().ceil(),
+        TsplibEdgeWeightType::Att => {
+            let r = ((dx * dx + dy * dy) / 10.0).sqrt();
+            let t = nint(r);
+            if t < r { t + 1.0 } else { t }
+        },
+        TsplibEdgeWeightType::Geo => {
+            let rrr = 6378.388;
+            let (lat1, long1) = (geo_radians(pos1[0]), geo_radians(pos1[1]));
+            let (lat2, long2) = (geo_radians(pos2[0]), geo_radians(pos2[1]));
+            let q1 = (long1 - long2).cos();
+            let q2 = (lat1 - lat2).cos();
+            let q3 = (lat1 + lat2).cos();
+            (rrr * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).acos() + 1.0).trunc()
+        },
+        TsplibEdgeWeightType::Explicit => panic!("explicit weights have no coordinates")
+    }
+}
+
+impl TsplibProblem {
+    // distances computed from the coordinates on demand, optionally with k nearest neighbours cached
+    pub fn coord_distances(&self, knn_count: Option<usize>) -> CoordDistances {
+        match knn_count {
+            Some(k) => CoordDistances::with_knn_cache(self.positions.clone(), self.edge_weight_type, k),
+            None => CoordDistances::new(self.positions.clone(), self.edge_weight_type)
+        }
+    }
+
+    // asymmetric explicit weights are kept only for ATSP instances
+    pub fn distances(&self) -> Distances {
+        if self.edge_weight_type != TsplibEdgeWeightType::Explicit {
+            let mut distances = DistanceHalfMatrix::new(self.dimension);
+            for v1 in 0..self.dimension {
+                for v2 in v1..self.dimension {
+                    let dist = if v1 == v2 { 0.0 } else { tsplib_distance(&self.positions[v1], &self.positions[v2], self.edge_weight_type) };
+                    distances.set(v1, v2, dist);
+                }
+            }
+            return distances.into();
+        }
+        let n = self.dimension;
+        let format = self.edge_weight_format.expect("missing EDGE_WEIGHT_FORMAT");
+        // (row, column) of every weight in the file order
+        let entries: Vec<(usize, usize)> = match format {
+            TsplibEdgeWeightFormat::FullMatrix => (0..n).flat_map(|i| (0..n).map(move |j| (i, j))).collect(),
+            TsplibEdgeWeightFormat::UpperRow => (0..n).flat_map(|i| (i + 1..n).map(move |j| (i, j))).collect(),
+            TsplibEdgeWeightFormat::LowerRow => (0..n).flat_map(|i| (0..i).map(move |j| (i, j))).collect(),
+            TsplibEdgeWeightFormat::UpperDiagRow => (0..n).flat_map(|i| (i..n).map(move |j| (i, j))).collect(),
+            TsplibEdgeWeightFormat::LowerDiagRow => (0..n).flat_map(|i| (0..=i).map(move |j| (i, j))).collect()
+        };
+        if entries.len() != self.edge_weights.len() {
+            panic!("incorrect number of edge weights");
+        }
+        if format != TsplibEdgeWeightFormat::FullMatrix {
+            let mut distances = DistanceHalfMatrix::new(n);
+            for (k, (i, j)) in entries.into_iter().enumerate() {
+                distances.set(i, j, self.edge_weights[k]);
+            }
+            return distances.into();
+        }
+        let mut distances = DistanceFullMatrix::new(n);
+        for (k, (i, j)) in entries.into_iter().enumerate() {
+            distances.set(i, j, self.edge_weights[k]);
+        }
+        let distances = Distances::from_full_matrix(distances);
+        if !distances.is_symmetric() && self.problem_type != "ATSP" {
+            panic!("asymmetric edge weights in a {} instance", self.problem_type);
+        }
+        distances
+    }
+}
+
+// Reads TOUR_SECTION of a TSPLIB tour up to -1, files without a header are read as one vertex per line
+pub fn load_opt_permutation(file_path: &str) -> TspPermutation {
+    let content = read_to_string(file_path).expect("file wasn't found.");
+    let tour = match content.find("TOUR_SECTION") {
+        Some(index) => &content[index + "TOUR_SECTION".len()..],
+        None => content.as_str()
+    };
+    let vert_perm: Vec<usize> = tour
+        .split_whitespace()
+        .map(|num_str| num_str.parse::<i64>().unwrap())
+        .take_while(|vert| *vert != -1)
+        .map(|vert| vert as usize - 1)
         .collect();
-    positions
+    TspPermutation { vert_perm }
+}
+
+// Saves the tour in the TSPLIB format with its length in the comment, it is read back by load_opt_permutation
+pub fn save_tsp_tour(file_path: &str, name: &str, solution: &BSFSingleObjSolution<TspPermutation>) {
+    let mut file = File::create(file_path).expect("unable to create a file.");
+    let tour = &solution.value.vert_perm;
+    file.write_all(format!("NAME : {}.tour\nCOMMENT : Length = {}\nTYPE : TOUR\nDIMENSION : {}\nTOUR_SECTION\n",
+        name, solution.fitness, tour.len()).as_bytes()).unwrap();
+    for vert in tour {
+        file.write_all(format!("{}\n", vert + 1).as_bytes()).unwrap();
+    }
+    file.write_all("-1\nEOF\n".as_bytes()).unwrap();
+}
+
+pub fn load_vert_positions(file_path: &str) -> Vec<[f64; 2]> {
+    load_tsplib(file_path).positions
 }
 
 pub fn vert_positions_to_distances(vert_positions: &Vec<[f64; 2]>) -> DistanceHalfMatrix {
     let mut distances = DistanceHalfMatrix::new(vert_positions.len());
     for v1 in 0..vert_positions.len() {
-        for v2 in 0..vert_positions.len() {
+        for v2 in 0..=v1 {
             let mut dist = 0.0f64;
             for d in 0..2 {
                 let diff = vert_positions[v1][d] - vert_positions[v2][d];
@@ -47,4 +269,54 @@ pub fn vert_positions_to_distances(vert_positions: &Vec<[f64; 2]>) -> DistanceHa
         }
     }
     distances
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::opt_traits::*;
+
+    fn opt_tour_len(name: &str) -> f64 {
+        let problem = load_tsplib(format!("data/tsp/{}.tsp", name).as_str());
+        let opt = load_opt_permutation(format!("data/tsp/{}.opt.tour", name).as_str());
+        TspFitness { distances: problem.distances() }.eval(&opt)
+    }
+
+    #[test]
+    fn test_published_optimal_lengths() {
+        assert_eq!(opt_tour_len("att48"), 10628.0);
+        assert_eq!(opt_tour_len("berlin52"), 7542.0);
+        assert_eq!(opt_tour_len("eil76"), 538.0);
+    }
+
+    #[test]
+    fn test_saved_tour_roundtrip() {
+        let file_path = std::env::temp_dir().join("eoa_test.tour");
+        let solution = BSFSingleObjSolution { value: TspPermutation { vert_perm: vec![2, 0, 3, 1] }, fitness: 10.0 };
+        save_tsp_tour(file_path.to_str().unwrap(), "test", &solution);
+        assert_eq!(load_opt_permutation(file_path.to_str().unwrap()).vert_perm, solution.value.vert_perm);
+    }
+
+    #[test]
+    fn test_asymmetric_weights() {
+        let atsp = parse_tsplib("NAME : test\nTYPE : ATSP\nDIMENSION : 3\nEDGE_WEIGHT_TYPE : EXPLICIT\n\
+            EDGE_WEIGHT_FORMAT : FULL_MATRIX\nEDGE_WEIGHT_SECTION\n0 1 9\n9 0 1\n1 9 0\nEOF\n");
+        let fitness = TspFitness { distances: atsp.distances() };
+        assert!(!fitness.distances.is_symmetric());
+        assert_eq!(fitness.eval(&TspPermutation { vert_perm: vec![0, 1, 2] }), 3.0);
+        assert_eq!(fitness.eval(&TspPermutation { vert_perm: vec![2, 1, 0] }), 27.0);
+    }
+
+    #[test]
+    fn test_explicit_formats_match() {
+        let header = "NAME : test\nTYPE : TSP\nDIMENSION : 3\nEDGE_WEIGHT_TYPE : EXPLICIT\n";
+        let full = parse_tsplib(&format!("{}EDGE_WEIGHT_FORMAT : FULL_MATRIX\nEDGE_WEIGHT_SECTION\n0 1 2\n1 0 3\n2 3 0\nEOF\n", header));
+        let upper = parse_tsplib(&format!("{}EDGE_WEIGHT_FORMAT : UPPER_ROW\nEDGE_WEIGHT_SECTION\n1 2\n3\nEOF\n", header));
+        let lower = parse_tsplib(&format!("{}EDGE_WEIGHT_FORMAT: LOWER_DIAG_ROW\nEDGE_WEIGHT_SECTION\n0 1 0\n2 3 0\nEOF\n", header));
+        for problem in [full, upper, lower] {
+            let distances = problem.distances();
+            assert_eq!([distances.get(0, 1), distances.get(0, 2), distances.get(2, 1)], [1.0, 2.0, 3.0]);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::opt_data::*;
+use crate::lower_bound::gap_summary;
 use std::{collections::{BinaryHeap, binary_heap::Iter}, io::{stdout, Write}};
 use crossterm::{QueueableCommand, cursor, terminal, ExecutableCommand};
 
@@ -33,6 +34,75 @@ pub fn bin_to_real_uniform(bits: &[u8], bounds: &[Bounds]) -> Option<Vec<f64>> {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BinaryEncoding {
+    Binary,
+    // reflected Gray code, neighbouring values differ in a single bit
+    Gray
+}
+
+// decodes bits with the most significant bit first, at most 64 bits
+pub fn decode_bits(bits: &[u8], encoding: BinaryEncoding) -> u64 {
+    let mut acc: u64 = 0;
+    let mut prev_bit: u8 = 0;
+    for bit in bits {
+        let binary_bit = match encoding {
+            BinaryEncoding::Binary => *bit,
+            BinaryEncoding::Gray => prev_bit ^ *bit
+        };
+        acc = (acc << 1) | binary_bit as u64;
+        prev_bit = binary_bit;
+    }
+    acc
+}
+
+pub fn encode_bits(value: u64, bits_count: usize, encoding: BinaryEncoding, res: &mut Vec<u8>) {
+    let code = match encoding {
+        BinaryEncoding::Binary => value,
+        BinaryEncoding::Gray => value ^ (value >> 1)
+    };
+    for b in (0..bits_count).rev() {
+        res.push(((code >> b) & 1) as u8);
+    }
+}
+
+// bits needed for every variable to have at least the given precision
+pub fn bits_for_precision(bounds: &[Bounds], precision: f64) -> Vec<usize> {
+    bounds.iter()
+        .map(|b| (((b.upper - b.lower) / precision + 1.0).log2().ceil() as usize).clamp(1, 64))
+        .collect()
+}
+
+// bits split evenly between the variables, remaining bits are unused as in bin_to_real_mut
+pub fn bits_uniform(bits_count: usize, variables_count: usize) -> Vec<usize> {
+    vec![bits_count / variables_count; variables_count]
+}
+
+// variable i is decoded from bits_counts[i] consecutive bits
+pub fn bin_to_real_encoded_mut(bits: &[u8], bounds: &[Bounds], bits_counts: &[usize], encoding: BinaryEncoding, res: &mut Vec<f64>) {
+    res.clear();
+    let mut from = 0;
+    for i in 0..bounds.len() {
+        let to = from + bits_counts[i];
+        let max_value = if bits_counts[i] >= 64 { u64::MAX } else { (1u64 << bits_counts[i]) - 1 };
+        let value = decode_bits(&bits[from..to], encoding);
+        let bound_size = bounds[i].upper - bounds[i].lower;
+        res.push(bounds[i].lower + bound_size * (value as f64) / (max_value as f64));
+        from = to;
+    }
+}
+
+// nearest encoded value for every variable, inverse of bin_to_real_encoded_mut
+pub fn real_to_bin_encoded(values: &[f64], bounds: &[Bounds], bits_counts: &[usize], encoding: BinaryEncoding) -> Vec<u8> {
+    let mut res = Vec::<u8>::with_capacity(bits_counts.iter().sum());
+    for i in 0..bounds.len() {
+        let max_value = if bits_counts[i] >= 64 { u64::MAX } else { (1u64 << bits_counts[i]) - 1 };
+        let ratio = ((values[i] - bounds[i].lower) / (bounds[i].upper - bounds[i].lower)).clamp(0.0, 1.0);
+        encode_bits((ratio * max_value as f64).round() as u64, bits_counts[i], encoding, &mut res);
+    }
+    res
+}
+
 pub struct LimitedBinaryHeap<T: Ord> {
     max_size: usize,
     heap: BinaryHeap<T>
@@ -70,6 +140,15 @@ pub fn squared_distance(p1: [f64; 2], p2: [f64; 2]) -> f64 {
     dist
 }
 
+// prints the gap of the final average fitness of every method, avg_stats contain sums over the repetitions
+pub fn print_avg_stats_gap(instance: &str, method_names: &Vec<&str>, avg_stats: &Vec<BSFSingleObjStatistics>, num_repetitions: usize, bound: f64)
+{
+    for s in 0..avg_stats.len() {
+        let found = avg_stats[s].fitness.last().unwrap() / num_repetitions as f64;
+        println!("{} {}: {}", instance, method_names[s], gap_summary(found, bound));
+    }
+}
+
 pub fn process_avg_stats(avg_stats: &mut Vec<BSFSingleObjStatistics>, opt_value: f64, num_iters: usize, num_repetitions: usize) -> f64
 {
     let mut fitness_min = opt_value;
@@ -542,6 +621,41 @@ mod tests {
         check_bin_to_real(&BITS34, &bounds, &[1.3333333333333333, 3.0, 4.666666666666666, 6.333333333333334, 8.0, 9.666666666666668]);
     }
 
+    #[test]
+    fn test_binary_encoding_matches_bin_to_real() {
+        let bounds = [Bounds { lower: -5.0, upper: 5.0 }, Bounds { lower: 0.0, upper: 10.0 }];
+        let bits_counts = bits_uniform(BITS29.len(), bounds.len());
+        let mut res = Vec::<f64>::new();
+        for bits in [BITS25, BITS29, BITS33, BITS34] {
+            bin_to_real_encoded_mut(&bits, &bounds, &bits_counts, BinaryEncoding::Binary, &mut res);
+            check_bin_to_real(&bits, &bounds, &res);
+        }
+    }
+
+    #[test]
+    fn test_gray_encoding() {
+        for bits_count in [1, 5, 12] {
+            let mut prev = Vec::<u8>::new();
+            for value in 0..(1u64 << bits_count) {
+                let mut bits = Vec::<u8>::new();
+                encode_bits(value, bits_count, BinaryEncoding::Gray, &mut bits);
+                assert_eq!(decode_bits(&bits, BinaryEncoding::Gray), value);
+                if value > 0 {
+                    let diff: usize = (0..bits_count).filter(|b| bits[*b] != prev[*b]).count();
+                    assert_eq!(diff, 1);
+                }
+                prev = bits;
+            }
+        }
+        let bounds = [Bounds { lower: -1.0, upper: 1.0 }, Bounds { lower: 0.0, upper: 100.0 }];
+        let bits_counts = bits_for_precision(&bounds, 0.01);
+        assert_eq!(bits_counts, vec![8, 14]);
+        let bits = real_to_bin_encoded(&[0.5, 42.0], &bounds, &bits_counts, BinaryEncoding::Gray);
+        let mut res = Vec::<f64>::new();
+        bin_to_real_encoded_mut(&bits, &bounds, &bits_counts, BinaryEncoding::Gray, &mut res);
+        assert!((res[0] - 0.5).abs() < 0.01 && (res[1] - 42.0).abs() < 0.01);
+    }
+
     #[test]
     fn test_limited_binary_heap() {
         let mut heap = LimitedBinaryHeap::<i32>::new(5);